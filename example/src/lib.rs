@@ -1,12 +1,23 @@
 #[cfg(test)]
 mod tests {
-    use mapack::Coordinate;
+    use mapack::cluster::Clusterer;
+    use mapack::{
+        Coordinate, DropMode, Limit, MemStore, Pyramid, TileStore, Value,
+    };
+    use mapack_fixtures::Rng;
+    use std::cmp::Reverse;
 
     mod my_tiles {
         mapack::mapack! {
             poi: {
                 name: String,
             },
+            #[zoom(12, 18)]
+            eatery: {
+                name: String,
+                category: u8,
+                is_private: bool,
+            },
         }
     }
 
@@ -24,7 +35,7 @@ mod tests {
             coordinate: coords.clone(),
         });
 
-        let pbf = my_tile.encode().expect("encode error");
+        let pbf = my_tile.encode();
 
         let old_tile = my_tiles::Tile::decode(
             coords.zoom(),
@@ -41,6 +52,642 @@ mod tests {
         assert_eq!(mp.id, op.id);
         assert!(mp.coordinate.distance_to(&op.coordinate) < 1.0, "distance");
 
-        assert_eq!(old_tile.encode().unwrap(), pbf);
+        assert_eq!(old_tile.encode(), pbf);
+    }
+
+    #[test]
+    fn cluster() {
+        let points = [
+            (35.55293745336477, 50.38793775563117, "a"),
+            (35.55294745336477, 50.38794775563117, "b"),
+            (35.55292745336477, 50.38795775563117, "c"),
+            (35.7, 51.4, "d"),
+        ]
+        .map(|(lat, lng, name)| my_tiles::PointPoi {
+            id: None,
+            name: name.to_string(),
+            coordinate: Coordinate::from_latlng(16, lat, lng),
+        });
+
+        let clusterer = Clusterer::new(4, 16);
+        let clusters = clusterer.clusters(&points);
+        assert_eq!(clusters[&16].len(), 2);
+        assert_eq!(clusters[&4].len(), 1);
+        assert_eq!(clusters[&4][0].point_count(), 4);
+        let deep = Clusterer::new(20, 30).clusters(&points);
+        assert_eq!(deep.keys().copied().collect::<Vec<_>>(), [20, 21, 22]);
+
+        let tiles = clusterer.tiles(&points, |members| {
+            let names = members.iter().map(|p| p.name.as_str()).collect();
+            vec![(String::from("names"), Value::from_string(names))]
+        });
+
+        let (tx, ty) = points[0].coordinate.txy();
+        let layer = &tiles[&(16, tx, ty)].layers[0];
+        assert_eq!(layer.name(), "poi");
+        assert_eq!(layer.features.len(), 1);
+
+        let feature = &layer.features[0];
+        let props = feature
+            .tags
+            .chunks(2)
+            .map(|kv| {
+                let (k, v) = (kv[0] as usize, kv[1] as usize);
+                (layer.keys[k].as_str(), &layer.values[v])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(props[0], ("cluster", &Value::from_bool(true)));
+        assert_eq!(props[1], ("point_count", &Value::from_uint(3)));
+        assert_eq!(props[2], ("names", &Value::from_string("abc".into())));
+    }
+
+    #[test]
+    fn pyramid() {
+        let points = [
+            (35.55293745336477, 50.38793775563117),
+            (35.55294745336477, 50.38794775563117),
+            (35.7, 51.4),
+        ]
+        .map(|(lat, lng)| my_tiles::PointPoi {
+            id: Some(1),
+            name: format!("{lat}"),
+            coordinate: Coordinate::from_latlng(16, lat, lng),
+        });
+
+        let mut store = MemStore::new();
+        let count = Pyramid::new(4, 12).build(points.clone(), &mut store);
+        // one tile on zoom 4 and 5, two on every other zoom
+        assert_eq!(count.unwrap(), 2 + 7 * 2);
+
+        let c = points[0].coordinate.with_zoom(12);
+        let pbf = store.get(12, c.tx(), c.ty()).unwrap().unwrap();
+        let tile = my_tiles::Tile::decode(12, c.tx(), c.ty(), pbf).unwrap();
+        assert_eq!(tile.poi.len(), 2);
+        assert_eq!(tile.poi[1].name, points[1].name);
+        assert!(tile.poi[0].coordinate.distance_to(&c) < 10.0);
+
+        // zoom levels past the maximum of a coordinate are clamped
+        let mut store = MemStore::new();
+        let count = Pyramid::new(23, 30).build(points.clone(), &mut store);
+        assert_eq!(count.unwrap(), 2);
+        let c = points[0].coordinate.with_zoom(22);
+        let pbf = store.get(22, c.tx(), c.ty()).unwrap().unwrap();
+        let tile = my_tiles::Tile::decode(22, c.tx(), c.ty(), pbf).unwrap();
+        assert!(tile.poi[0].coordinate.distance_to(&c) < 1.0);
+    }
+
+    #[test]
+    fn limit() {
+        // a dense blob of points and a few spread over the same tile
+        let mut points = (0..50)
+            .map(|i| (35.6 + i as f64 * 1e-6, 50.4, i))
+            .chain((0..5).map(|i| (35.6 + i as f64 * 0.02, 50.45, 100 + i)))
+            .map(|(lat, lng, id)| my_tiles::PointPoi {
+                id: Some(id),
+                name: String::new(),
+                coordinate: Coordinate::from_latlng(10, lat, lng),
+            })
+            .collect::<Vec<_>>();
+        points.reverse();
+
+        let c = points[0].coordinate.clone();
+        let decode = |store: &MemStore| {
+            let pbf = store.get(10, c.tx(), c.ty()).unwrap().unwrap();
+            let tile = my_tiles::Tile::decode(10, c.tx(), c.ty(), pbf).unwrap();
+            let mut ids =
+                tile.poi.iter().map(|p| p.id.unwrap()).collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        let mut store = MemStore::new();
+        Pyramid::new(10, 10)
+            .with_limit(Limit::new().with_max_features(10))
+            .build_by_key(points.clone(), &mut store, |p| Reverse(p.id))
+            .unwrap();
+        assert_eq!(decode(&store), (0..10).collect::<Vec<_>>());
+
+        let mut store = MemStore::new();
+        let limit =
+            Limit::new().with_max_features(10).with_mode(DropMode::Densest);
+        Pyramid::new(10, 10)
+            .with_limit(limit)
+            .build_by_key(points.clone(), &mut store, |p| Reverse(p.id))
+            .unwrap();
+        let ids = decode(&store);
+        assert!(ids.len() <= 10);
+        assert!((100..105).all(|id| ids.contains(&id)), "{ids:?}");
+
+        let mut store = MemStore::new();
+        Pyramid::new(10, 10)
+            .with_limit(Limit::new().with_max_bytes(200))
+            .build(points.clone(), &mut store)
+            .unwrap();
+        let pbf = store.get(10, c.tx(), c.ty()).unwrap().unwrap();
+        assert!(pbf.len() <= 200);
+
+        // not even a single point fits, nothing is written
+        let mut store = MemStore::new();
+        let count = Pyramid::new(10, 10)
+            .with_limit(Limit::new().with_max_bytes(8))
+            .build(points, &mut store)
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(store.get(10, c.tx(), c.ty()).unwrap().is_none());
+    }
+
+    #[test]
+    fn overzoom() {
+        let mut tile = my_tiles::Tile::new();
+        for (lat, lng) in [
+            (35.55293745336477, 50.38793775563117),
+            (35.55594745336477, 50.38494775563117),
+            (35.51, 50.41),
+        ] {
+            let mut point =
+                my_tiles::PointPoi::new(Coordinate::from_latlng(14, lat, lng));
+            point.name = format!("{lat}");
+            tile.poi.push(point);
+        }
+
+        let (tx, ty) = tile.poi[0].coordinate.txy();
+        let pbf = tile.encode();
+
+        let child = tile.poi[0].coordinate.with_zoom(17);
+        let (ctx, cty) = child.txy();
+        let over: my_tiles::Tile =
+            mapack::overzoom(14, tx, ty, pbf.clone(), 17, ctx, cty).unwrap();
+        assert_eq!(over.poi.len(), 1);
+        assert_eq!(over.poi[0].name, tile.poi[0].name);
+        assert_eq!(over.poi[0].coordinate.zoom(), 17);
+        assert_eq!(over.poi[0].coordinate.txy(), (ctx, cty));
+        assert!(over.poi[0].coordinate.distance_to(&child) < 10.0);
+
+        let over: my_tiles::Tile =
+            mapack::overzoom(14, tx, ty, pbf.clone(), 17, ctx + 100, cty)
+                .unwrap();
+        assert!(over.poi.is_empty());
+
+        let deep = tile.poi[0].coordinate.with_zoom(22).txy();
+        let (dtx, dty) = (deep.0 * 2, deep.1 * 2);
+        let over: my_tiles::Tile =
+            mapack::overzoom(14, tx, ty, pbf, 23, dtx, dty).unwrap();
+        assert!(over.poi.is_empty());
+    }
+
+    #[test]
+    fn merge_children() {
+        let (tx, ty) = (10516, 6458);
+        let points = [(1000, 1000), (3000, 1000), (1000, 3000), (3000, 3100)]
+            .map(|(sx, sy)| {
+                let c = Coordinate::from_screen(14, tx, ty, sx, sy);
+                let mut point = my_tiles::PointPoi::new(c.with_zoom(16));
+                point.name = format!("{sx}:{sy}");
+                point
+            });
+
+        let mut store = MemStore::new();
+        Pyramid::new(15, 15).build(points.clone(), &mut store).unwrap();
+        let children = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .map(|(x, y)| store.get(15, tx * 2 + x, ty * 2 + y).unwrap());
+        assert!(children.iter().all(|c| c.is_some()));
+
+        let pbf = mapack::merge_children::<my_tiles::Tile>(
+            14,
+            tx,
+            ty,
+            children.clone(),
+            None,
+        )
+        .unwrap();
+        let parent = my_tiles::Tile::decode(14, tx, ty, pbf).unwrap();
+        assert_eq!(parent.poi.len(), 4);
+        for point in points.iter() {
+            let c = point.coordinate.with_zoom(14);
+            let merged =
+                parent.poi.iter().find(|p| p.name == point.name).unwrap();
+            assert_eq!(merged.coordinate.zoom(), 14);
+            assert_eq!(merged.coordinate.sxy(), c.sxy());
+        }
+
+        let limit = Limit::new().with_max_features(2);
+        let pbf = mapack::merge_children::<my_tiles::Tile>(
+            14,
+            tx,
+            ty,
+            children.clone(),
+            Some(&limit),
+        )
+        .unwrap();
+        let parent = my_tiles::Tile::decode(14, tx, ty, pbf).unwrap();
+        let names = parent.poi.iter().map(|p| &p.name[..]).collect::<Vec<_>>();
+        // the north west and south east children, not the northern ones.
+        // screen y grows to the north
+        assert_eq!(names, ["1000:3000", "3000:1000"]);
+
+        let full = mapack::merge_children::<my_tiles::Tile>(
+            14,
+            tx,
+            ty,
+            children.clone(),
+            None,
+        )
+        .unwrap();
+        let limit = Limit::new().with_max_bytes(full.len() - 1);
+        let pbf = mapack::merge_children::<my_tiles::Tile>(
+            14,
+            tx,
+            ty,
+            children.clone(),
+            Some(&limit),
+        )
+        .unwrap();
+        assert!(pbf.len() < full.len());
+        let parent = my_tiles::Tile::decode(14, tx, ty, pbf).unwrap();
+        assert!(!parent.poi.is_empty() && parent.poi.len() < 4);
+
+        // tiles without children do not overflow
+        let pbf = mapack::merge_children::<my_tiles::Tile>(
+            255,
+            u32::MAX,
+            u32::MAX,
+            children,
+            None,
+        )
+        .unwrap();
+        assert_eq!(pbf, my_tiles::Tile::new().encode());
+    }
+
+    #[test]
+    fn geojson() {
+        let mut tile = my_tiles::Tile::new();
+        let mut point = my_tiles::PointPoi::new(Coordinate::from_latlng(
+            14,
+            35.55293745336477,
+            50.38793775563117,
+        ));
+        point.id = Some(7);
+        point.name = String::from("cafe");
+        tile.poi.push(point);
+
+        let (tx, ty) = tile.poi[0].coordinate.txy();
+        let pbf = tile.encode();
+        let decoded = my_tiles::Tile::decode(14, tx, ty, pbf.clone()).unwrap();
+        let raw = mapack::Tile::decode(&pbf).unwrap();
+
+        let json = decoded.to_geojson();
+        assert_eq!(json, raw.to_geojson(14, tx, ty));
+
+        let feature = &json["poi"]["features"][0];
+        assert_eq!(json["poi"]["type"], "FeatureCollection");
+        assert_eq!(feature["id"], 7);
+        assert_eq!(feature["properties"]["name"], "cafe");
+        assert_eq!(feature["geometry"]["type"], "Point");
+        let lng = feature["geometry"]["coordinates"][0].as_f64().unwrap();
+        let lat = feature["geometry"]["coordinates"][1].as_f64().unwrap();
+        assert!((lat - 35.55293745336477).abs() < 1e-3);
+        assert!((lng - 50.38793775563117).abs() < 1e-3);
+
+        // a smaller extent is scaled, buffer points are not clamped
+        let mut raw = raw;
+        let layer = &mut raw.layers[0];
+        layer.extent = Some(512);
+        layer.features[0].geometry = vec![9, 512, 512];
+        let mut buffered = layer.features[0].clone();
+        buffered.geometry = vec![9, 127, 512];
+        layer.features.push(buffered);
+        let json = raw.to_geojson(14, tx, ty);
+        let features = &json["poi"]["features"];
+        let center = Coordinate::from_screen(14, tx, ty, 2048, 2048);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"],
+            mapack::serde_json::json!([center.longitude(), center.latitude()])
+        );
+        assert!(features[1]["geometry"].is_null());
+    }
+
+    #[test]
+    fn from_geojson() {
+        use mapack::geojson::GeoJsonError;
+        use mapack::serde_json::json;
+
+        let mut feature = json!({
+            "type": "Feature",
+            "id": 12,
+            "geometry": {
+                "type": "Point",
+                "coordinates": [50.38793775563117, 35.55293745336477],
+            },
+            "properties": { "name": "kabab", "category": 3, "extra": 1 },
+        });
+
+        let eatery =
+            my_tiles::PointEatery::from_geojson_feature(16, &feature).unwrap();
+        assert_eq!(eatery.id, Some(12));
+        assert_eq!(eatery.name, "kabab");
+        assert_eq!(eatery.category, 3);
+        assert!(!eatery.is_private);
+        assert_eq!(eatery.coordinate.zoom(), 16);
+        assert_eq!(eatery.coordinate.latitude(), 35.55293745336477);
+
+        feature["properties"]["category"] = json!(300);
+        let err = my_tiles::PointEatery::from_geojson_feature(16, &feature);
+        assert_eq!(
+            err.unwrap_err(),
+            GeoJsonError::BadProperty { key: "category", expected: "u8" }
+        );
+
+        feature["properties"]["category"] = json!(1);
+        feature["properties"]["is_private"] = json!("yes");
+        let err = my_tiles::PointEatery::from_geojson_feature(16, &feature);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "property is_private is not a valid bool"
+        );
+
+        feature["properties"]["is_private"] = json!(false);
+        for coordinates in [json!([50.38, 95.0]), json!([190.0, 35.55])] {
+            feature["geometry"]["coordinates"] = coordinates;
+            let err = my_tiles::PointEatery::from_geojson_feature(16, &feature);
+            assert_eq!(err.unwrap_err(), GeoJsonError::BadGeometry);
+        }
+
+        feature["geometry"]["type"] = json!("LineString");
+        let err = my_tiles::PointEatery::from_geojson_feature(16, &feature);
+        assert_eq!(err.unwrap_err(), GeoJsonError::BadGeometry);
+    }
+
+    #[test]
+    fn csv() {
+        use mapack::csv::{CsvReader, RowError};
+
+        let data = "\
+lat;lng;id;name;category;is_private;note
+35.55;50.38;1;kabab;3;false;x
+35.56;50.39;;pizza;;true;
+bad;50.38;3;sushi;1;false;
+35.57;50.40;4;falafel;1000;false;
+35.58;50.41;5;soup;2;nope;
+NaN;50.41;6;;;;
+95.0;50.41;7;;;;
+35.58;inf;8;;;;
+35.58;-180.5;9;;;;
+";
+        let report = CsvReader::new(16)
+            .with_latlng_columns("lat", "lng")
+            .with_id_column("id")
+            .with_delimiter(b';')
+            .read::<my_tiles::PointEatery, _>(data.as_bytes())
+            .unwrap();
+
+        assert_eq!(report.points.len(), 2);
+        assert_eq!(report.points[0].id, Some(1));
+        assert_eq!(report.points[0].name, "kabab");
+        assert_eq!(report.points[0].category, 3);
+        assert_eq!(report.points[0].coordinate.latitude(), 35.55);
+        assert_eq!(report.points[1].id, None);
+        assert!(report.points[1].is_private);
+
+        assert_eq!(
+            report.errors,
+            vec![
+                (4, RowError::BadLatitude(String::from("bad"))),
+                (
+                    5,
+                    RowError::BadField {
+                        key: String::from("category"),
+                        expected: "u8"
+                    }
+                ),
+                (
+                    6,
+                    RowError::BadField {
+                        key: String::from("is_private"),
+                        expected: "bool"
+                    }
+                ),
+                (7, RowError::BadLatitude(String::from("NaN"))),
+                (8, RowError::BadLatitude(String::from("95.0"))),
+                (9, RowError::BadLongitude(String::from("inf"))),
+                (10, RowError::BadLongitude(String::from("-180.5"))),
+            ]
+        );
+
+        let missing = CsvReader::new(16)
+            .read::<my_tiles::PointEatery, _>("lat,lng\n".as_bytes());
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn tilejson() {
+        use mapack::tilejson::TileJson;
+
+        let layers = my_tiles::Tile::VECTOR_LAYERS;
+        assert_eq!(layers[0].id, "poi");
+        assert_eq!(layers[0].fields, [("name", "String")]);
+        assert_eq!(layers[1].minzoom, Some(12));
+        assert_eq!(
+            layers[1].fields,
+            [
+                ("name", "String"),
+                ("category", "Number"),
+                ("is_private", "Boolean")
+            ]
+        );
+
+        let tj = TileJson::new(["https://example.com/{z}/{x}/{y}.pbf"])
+            .with_name("my tiles")
+            .with_zoom(4, 16);
+        let doc = my_tiles::Tile::tilejson(&tj);
+        assert_eq!(doc["tilejson"], "3.0.0");
+        assert_eq!(doc["name"], "my tiles");
+        assert_eq!(doc["tiles"][0], "https://example.com/{z}/{x}/{y}.pbf");
+        assert!(doc.get("attribution").is_none());
+
+        let poi = &doc["vector_layers"][0];
+        assert_eq!((&poi["minzoom"], &poi["maxzoom"]), (&4.into(), &16.into()));
+        let eatery = &doc["vector_layers"][1];
+        assert_eq!(eatery["fields"]["category"], "Number");
+        assert_eq!(eatery["minzoom"], 12);
+        assert_eq!(eatery["maxzoom"], 16);
+    }
+
+    #[test]
+    fn compressed() {
+        let mut tile = my_tiles::Tile::new();
+        let c =
+            Coordinate::from_latlng(14, 35.55293745336477, 50.38793775563117);
+        for i in 0..50 {
+            let mut point = my_tiles::PointEatery::new(c.clone());
+            point.id = Some(i);
+            point.name = String::from("kebab house");
+            tile.eatery.push(point);
+        }
+        let (tx, ty) = c.txy();
+
+        let raw = tile.encode();
+        let gz = tile.encode_compressed(mapack::Compression::Gzip).unwrap();
+        assert_eq!(&gz[..2], [0x1f, 0x8b]);
+        assert!(gz.len() < raw.len());
+
+        let decoded = my_tiles::Tile::decode(14, tx, ty, gz).unwrap();
+        assert_eq!(decoded.eatery.len(), 50);
+        assert_eq!(decoded.eatery[49].name, "kebab house");
+    }
+
+    /// the layer `write_layer` streams, built field by field
+    fn reference_layer<P: mapack::Point>(points: &[P]) -> mapack::Layer {
+        let keys = P::KEYS.len() as u32;
+        let features = points
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| mapack::Feature {
+                id: p.id(),
+                tags: (0..keys)
+                    .flat_map(|k| [k, idx as u32 * keys + k])
+                    .collect(),
+                geom_type: Some(mapack::GeomType::Point),
+                geometry: p.coordinate().to_geometry().to_vec(),
+            })
+            .collect();
+
+        mapack::Layer {
+            version: Some(2),
+            name: Some(P::NAME.to_string()),
+            features,
+            keys: P::KEYS.iter().map(|k| k.to_string()).collect(),
+            values: points.iter().flat_map(|p| p.values()).collect(),
+            extent: Some(4096),
+        }
+    }
+
+    #[test]
+    fn encode_to() {
+        let mut tile = my_tiles::Tile::new();
+        let c =
+            Coordinate::from_latlng(14, 35.55293745336477, 50.38793775563117);
+        for i in 0..20 {
+            let mut point = my_tiles::PointEatery::new(c.clone());
+            point.id = Some(i);
+            point.name = format!("eatery {i}");
+            point.category = i as u8;
+            point.is_private = i % 2 == 0;
+            tile.eatery.push(point);
+        }
+        tile.poi.push(my_tiles::PointPoi::new(c.clone()));
+
+        let mut streamed = std::io::BufWriter::new(Vec::new());
+        tile.encode_to(&mut streamed).unwrap();
+        let streamed = streamed.into_inner().unwrap();
+        let built = mapack::Tile {
+            layers: vec![
+                reference_layer(&tile.poi),
+                reference_layer(&tile.eatery),
+            ],
+        };
+        assert_eq!(streamed, built.encode());
+
+        let raw = mapack::Tile::decode(&streamed).unwrap();
+        assert!(mapack::validate(&raw).is_empty());
+
+        let (tx, ty) = c.txy();
+        let decoded = my_tiles::Tile::decode(14, tx, ty, streamed).unwrap();
+        assert_eq!(decoded.eatery.len(), 20);
+        assert_eq!(decoded.eatery[7].name, "eatery 7");
+        assert_eq!(decoded.eatery[7].category, 7);
+        assert!(!decoded.eatery[7].is_private);
+    }
+
+    #[test]
+    fn hostile_decode() {
+        use mapack::{GeomType, LayerBuilder, Tile};
+
+        let mut layer = LayerBuilder::new("eatery");
+        let name = || [("name", Value::from_string(String::from("x")))];
+        layer.push_geometry(
+            Some(1),
+            GeomType::Point,
+            vec![9, u32::MAX, 1],
+            name(),
+        );
+        layer.push_geometry(Some(2), GeomType::Point, vec![9, 4, 4, 4], name());
+        layer.push_geometry(Some(3), GeomType::Point, vec![9, 4, 4], name());
+        // buffer points of another producer, 64 units past the edges
+        layer.push_geometry(Some(4), GeomType::Point, vec![9, 127, 4], name());
+        layer.push_geometry(Some(5), GeomType::Point, vec![9, 8320, 4], name());
+        layer.push_geometry(Some(6), GeomType::Point, vec![9, 4, 8320], name());
+        layer.push_geometry(Some(7), GeomType::Point, vec![9, 8192, 0], name());
+        let pbf = Tile { layers: vec![layer.build()] }.encode();
+
+        let (tile, skipped) =
+            my_tiles::Tile::decode_skipped(14, 10680, 6499, pbf.clone())
+                .unwrap();
+        assert_eq!(skipped, 5);
+        let ids = tile.eatery.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ids, [Some(3), Some(7)]);
+        assert_eq!(tile.eatery[1].coordinate.sxy(), (4096, 4096));
+        let decoded =
+            my_tiles::Tile::decode(14, 10680, 6499, pbf.clone()).unwrap();
+        assert_eq!(decoded.eatery.len(), 2);
+
+        for len in 0..pbf.len() {
+            let _ =
+                my_tiles::Tile::decode(14, 10680, 6499, pbf[..len].to_vec());
+        }
+    }
+
+    #[test]
+    fn query() {
+        use mapack::query::{BBox, PointQuery};
+
+        let mut tile = my_tiles::Tile::new();
+        let mut rng = Rng(7);
+        for i in 0..500 {
+            let lat = 35.5 + (rng.next_u32() % 1000) as f64 / 10000.0;
+            let lng = 50.3 + (rng.next_u32() % 1000) as f64 / 10000.0;
+            let mut point =
+                my_tiles::PointPoi::new(Coordinate::from_latlng(16, lat, lng));
+            point.id = Some(i);
+            tile.poi.push(point);
+        }
+        for (i, lng) in [(500, 179.9995), (501, -179.9995)] {
+            let c = Coordinate::from_latlng(16, 10.0, lng);
+            let mut point = my_tiles::PointPoi::new(c);
+            point.id = Some(i);
+            tile.poi.push(point);
+        }
+
+        let index = tile.poi.index();
+        let ids = |points: Vec<&my_tiles::PointPoi>| {
+            points.iter().map(|p| p.id.unwrap()).collect::<Vec<_>>()
+        };
+
+        let center = Coordinate::from_latlng(16, 35.55, 50.35);
+        let near = tile.poi.within_radius(&center, 1000.0);
+        assert!(!near.is_empty() && near.len() < 500);
+        assert!(near.iter().all(|p| p.coordinate.distance_to(&center) <= 1e3));
+        assert_eq!(ids(index.within_radius(&center, 1000.0)), ids(near));
+
+        let bbox = BBox::new(50.32, 35.52, 50.34, 35.54);
+        let inside = tile.poi.within_bbox(&bbox);
+        assert!(!inside.is_empty());
+        assert_eq!(ids(index.within_bbox(&bbox)), ids(inside));
+
+        let nearest = tile.poi.nearest(&center, 10);
+        assert_eq!(nearest.len(), 10);
+        assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+        let pairs = |found: Vec<(&my_tiles::PointPoi, f64)>| {
+            found.iter().map(|(p, d)| (p.id, *d)).collect::<Vec<_>>()
+        };
+        assert_eq!(pairs(index.nearest(&center, 10)), pairs(nearest));
+
+        let dateline = Coordinate::from_latlng(16, 10.0, 180.0);
+        let across = tile.poi.within_radius(&dateline, 100.0);
+        assert_eq!(ids(across), [500, 501]);
+        assert_eq!(ids(index.within_radius(&dateline, 100.0)), [500, 501]);
+        let bbox = BBox::new(179.0, 9.0, -179.0, 11.0);
+        assert_eq!(ids(index.within_bbox(&bbox)), [500, 501]);
+        let nearest = index.nearest(&dateline, 2);
+        assert_eq!(ids(nearest.iter().map(|p| p.0).collect()), [500, 501]);
     }
 }
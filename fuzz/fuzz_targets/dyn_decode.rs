@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mapack::DynTile;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tile) = DynTile::decode(14, 10680, 6499, data.to_vec()) else {
+        return;
+    };
+    let pbf = tile.encode();
+    let again = DynTile::decode(14, 10680, 6499, pbf).expect("no decode");
+    mapack::diff(&tile, &again);
+});
@@ -0,0 +1,29 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+mod fuzz_tiles {
+    mapack::mapack! {
+        poi: {
+            name: String,
+            rank: u32,
+            delta: i16,
+            is_open: bool,
+        },
+        empty: {},
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&zom, pbf)) = data.split_first() else { return };
+    let n = 1u32 << (zom % 23);
+    let (tx, ty) = (n / 3, n - 1);
+
+    let Ok(tile) = fuzz_tiles::Tile::decode(zom, tx, ty, pbf.to_vec()) else {
+        return;
+    };
+    let again = fuzz_tiles::Tile::decode(zom, tx, ty, tile.encode())
+        .expect("re-encoded tile does not decode");
+    assert_eq!(again.poi.len(), tile.poi.len());
+    tile.to_geojson();
+});
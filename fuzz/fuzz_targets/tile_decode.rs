@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mapack::Tile;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tile) = Tile::decode(data) else { return };
+    mapack::validate(&tile);
+
+    let pbf = tile.encode();
+    let again = Tile::decode(&pbf).expect("re-encoded tile does not decode");
+    assert_eq!(again.encode(), pbf);
+});
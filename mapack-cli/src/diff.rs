@@ -0,0 +1,18 @@
+use crate::{Result, TileArgs};
+use mapack::DynTile;
+use std::io::Write;
+use std::path::Path;
+
+/// returns `false` if the tiles differ
+pub fn run(old: &TileArgs, new: &Path, out: &mut impl Write) -> Result<bool> {
+    let (zom, tx, ty) = old.zxy()?;
+    let a = DynTile::decode(zom, tx, ty, std::fs::read(&old.file)?)?;
+    let b = DynTile::decode(zom, tx, ty, std::fs::read(new)?)?;
+
+    let changes = mapack::diff(&a, &b);
+    for change in changes.iter() {
+        writeln!(out, "{change}")?;
+    }
+
+    Ok(changes.is_empty())
+}
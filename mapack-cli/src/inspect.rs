@@ -0,0 +1,46 @@
+use crate::{Result, TileArgs};
+use mapack::Tile;
+use std::io::Write;
+
+pub fn run(args: &TileArgs, out: &mut impl Write) -> Result<()> {
+    let (zom, tx, ty) = args.zxy()?;
+    let pbf = std::fs::read(&args.file)?;
+    let len = pbf.len();
+    let tile = Tile::decode(&mapack::decompress(pbf)?)?;
+
+    writeln!(out, "tile {zom}/{tx}/{ty}: {len} bytes")?;
+    for layer in tile.layers.iter() {
+        writeln!(out, "layer {:?}", layer.name())?;
+        writeln!(out, "  version: {}", layer.version())?;
+        writeln!(out, "  extent: {}", layer.extent())?;
+        writeln!(out, "  keys: {}", layer.keys.len())?;
+        writeln!(out, "  values: {}", layer.values.len())?;
+        writeln!(out, "  features: {}", layer.features.len())?;
+
+        for feature in layer.features.iter() {
+            let id = match feature.id {
+                Some(id) => format!("#{id}"),
+                None => String::from("#-"),
+            };
+
+            let point = layer
+                .points(zom, tx, ty, feature)
+                .filter(|p| p.len() == 1)
+                .map(|mut p| p.remove(0));
+            match point {
+                Some(c) => {
+                    let (lat, lng) = c.latlng();
+                    writeln!(out, "    {id} point {lat:.7}, {lng:.7}")?;
+                }
+                None => writeln!(
+                    out,
+                    "    {id} {:?} with {} geometry integers",
+                    feature.geom_type(),
+                    feature.geometry.len()
+                )?,
+            }
+        }
+    }
+
+    Ok(())
+}
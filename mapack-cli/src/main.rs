@@ -0,0 +1,110 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+mod diff;
+mod inspect;
+mod validate;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "mapack", version, about = "mapbox vector tile tools")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// print the layers and the points of a tile
+    Inspect(TileArgs),
+    /// check tiles against the MVT 2.1 spec
+    Validate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// print the features added, removed, moved or changed between tiles
+    Diff {
+        #[command(flatten)]
+        old: TileArgs,
+        /// tile file at the same position
+        new: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct TileArgs {
+    /// tile file, the position is read from `{z}/{x}/{y}.pbf` paths
+    file: PathBuf,
+    /// zoom of the tile
+    #[arg(short, long)]
+    z: Option<u8>,
+    /// column of the tile
+    #[arg(short, long)]
+    x: Option<u32>,
+    /// row of the tile
+    #[arg(short, long)]
+    y: Option<u32>,
+}
+
+impl TileArgs {
+    fn zxy(&self) -> Result<(u8, u32, u32)> {
+        let mut parts = self.file.with_extension("");
+        let mut part = || {
+            let p = parts.file_name()?.to_str()?.to_string();
+            parts.pop();
+            Some(p)
+        };
+        let (y, x, z) = (part(), part(), part());
+
+        let z = self.z.or_else(|| z?.parse().ok());
+        let x = self.x.or_else(|| x?.parse().ok());
+        let y = self.y.or_else(|| y?.parse().ok());
+        match (z, x, y) {
+            (Some(z), Some(x), Some(y)) => Ok((z, x, y)),
+            _ => Err("missing tile position, use --z --x --y".into()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut out = std::io::stdout().lock();
+
+    let result = match &cli.command {
+        Command::Inspect(args) => inspect::run(args, &mut out).map(|_| true),
+        Command::Validate { files } => validate::run(files, &mut out),
+        Command::Diff { old, new } => diff::run(old, new, &mut out),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TileArgs;
+
+    #[test]
+    fn zxy() {
+        let args =
+            |file: &str, z| TileArgs { file: file.into(), z, x: None, y: None };
+
+        assert_eq!(
+            args("tiles/12/2621/1614.pbf", None).zxy().unwrap(),
+            (12, 2621, 1614)
+        );
+        assert_eq!(
+            args("a/2621/1614.pbf", Some(12)).zxy().unwrap(),
+            (12, 2621, 1614)
+        );
+        assert!(args("tile.pbf", None).zxy().is_err());
+    }
+}
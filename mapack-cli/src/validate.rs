@@ -0,0 +1,39 @@
+use crate::Result;
+use mapack::Tile;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// returns `false` if any of the tiles is invalid, warnings are printed
+/// but do not fail
+pub fn run(files: &[PathBuf], out: &mut impl Write) -> Result<bool> {
+    let mut valid = true;
+
+    for file in files {
+        let pbf = std::fs::read(file)?;
+        let tile = mapack::decompress(pbf)
+            .map_err(mapack::Error::from)
+            .and_then(|pbf| Tile::decode(&pbf));
+        let tile = match tile {
+            Ok(tile) => tile,
+            Err(e) => {
+                writeln!(out, "{}: not a tile: {e}", file.display())?;
+                valid = false;
+                continue;
+            }
+        };
+
+        let violations = mapack::validate(&tile);
+        for violation in violations.iter() {
+            let level =
+                if violation.rule.is_warning() { "warning: " } else { "" };
+            writeln!(out, "{}: {level}{violation}", file.display())?;
+        }
+        if violations.iter().all(|v| v.rule.is_warning()) {
+            writeln!(out, "{}: ok", file.display())?;
+        } else {
+            valid = false;
+        }
+    }
+
+    Ok(valid)
+}
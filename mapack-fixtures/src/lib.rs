@@ -0,0 +1,14 @@
+//! Fixture generators shared by the tests and benches of the workspace.
+//! only ever a dev-dependency.
+
+/// xorshift, so every run sees the same fixture
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 32) as u32
+    }
+}
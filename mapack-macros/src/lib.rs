@@ -45,16 +45,43 @@ struct Layer {
     ident: syn::Ident,
     name: syn::Ident,
     fields: Vec<Field>,
+    zoom: Option<(u8, u8)>,
 }
 
 impl syn::parse::Parse for Layer {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut zoom = None;
+
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        for attr in attrs {
+            if !attr.path().is_ident("zoom") {
+                continue;
+            }
+            let args = attr.parse_args_with(
+                syn::punctuated::Punctuated::<syn::LitInt, syn::Token![,]>::parse_terminated,
+            )?;
+            let args = args
+                .iter()
+                .map(|a| a.base10_parse::<u8>())
+                .collect::<syn::Result<Vec<_>>>()?;
+            match args[..] {
+                [min, max] if min <= max => zoom = Some((min, max)),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "expected #[zoom(min, max)] with min <= max",
+                    ))
+                }
+            }
+        }
+
         let name = input.parse::<syn::Ident>()?;
 
         let mut layer = Self {
             ident: format_ident!("Point{}", to_camelcase(&name.to_string())),
             name,
             fields: Vec::new(),
+            zoom,
         };
 
         input.parse::<syn::Token![:]>()?;
@@ -94,6 +121,12 @@ impl syn::parse::Parse for Tile {
     }
 }
 
+/// Generates a `Tile` with a `Vec` of points for every layer and a
+/// `Point*` struct for every layer with its fields.
+///
+/// Decoding only keeps features that are a single point inside of the
+/// tile. points in the buffer around it, outside of `0..=4096`, are
+/// skipped and counted by `Tile::decode_skipped`.
 #[proc_macro]
 pub fn mapack(code: TokenStream) -> TokenStream {
     let tile = syn::parse_macro_input!(code as Tile);
@@ -103,7 +136,7 @@ pub fn mapack(code: TokenStream) -> TokenStream {
     quote_into! {s +=
         #{
             for layer in tile.layers.iter() {
-                let Layer { ident, fields, name } = layer;
+                let Layer { ident, fields, name, .. } = layer;
                 let name_str = name.to_string();
                 let keys_len = fields.len();
                 quote_into! {s +=
@@ -143,22 +176,42 @@ pub fn mapack(code: TokenStream) -> TokenStream {
                             #{point_decode(s, layer)}
                         }
 
+                        /// Points of `layer` and the number of features skipped
+                        /// for not being a single point inside of the tile,
+                        /// see [`Coordinate::try_from_geometry`](#ci::Coordinate::try_from_geometry).
                         pub fn decode_layer(
                             zom: u8, tx: u32, ty: u32,
                             layer: &#ci::Layer
-                        ) -> #ci::protobuf::Result<Vec<Self>> {
+                        ) -> Result<(Vec<Self>, usize), #ci::Error> {
                             let mut points = Vec::<Self>::with_capacity(layer.features.len());
+                            let mut skipped = 0;
 
                             for feature in layer.features.iter() {
                                 match Self::decode_point(zom, tx, ty, feature, &layer.values) {
                                     Ok(v) => points.push(v),
-                                    Err(e) => {
-                                        println!("found an invalid marker: {e:?}")
-                                    }
+                                    Err(_) => skipped += 1,
                                 }
                             }
 
-                            Ok(points)
+                            Ok((points, skipped))
+                        }
+
+                        /// Reads a GeoJSON `Point` feature, properties are
+                        /// matched to the fields by their key.
+                        #[allow(dead_code)]
+                        pub fn from_geojson_feature(
+                            zom: u8, feature: &#ci::serde_json::Value,
+                        ) -> Result<Self, #ci::geojson::GeoJsonError> {
+                            let (id, coordinate) = #ci::geojson::feature_point(zom, feature)?;
+                            let mut point = Self::new(coordinate);
+                            point.id = id;
+
+                            let properties = &feature["properties"];
+                            #{for field in fields {
+                                point_geojson_decode(s, field);
+                            }}
+
+                            Ok(point)
                         }
 
                         #{for field in fields {
@@ -181,6 +234,64 @@ pub fn mapack(code: TokenStream) -> TokenStream {
                             }
                         }}
                     }
+
+                    impl #ci::Point for #ident {
+                        const NAME: &'static str = #name_str;
+                        const KEYS: &'static [&'static str] = &#ident::KEYS;
+
+                        fn new(coordinate: #ci::Coordinate) -> Self {
+                            #ident::new(coordinate)
+                        }
+
+                        fn coordinate(&self) -> &#ci::Coordinate {
+                            &self.coordinate
+                        }
+
+                        fn coordinate_mut(&mut self) -> &mut #ci::Coordinate {
+                            &mut self.coordinate
+                        }
+
+                        fn id(&self) -> Option<u64> {
+                            self.id
+                        }
+
+                        fn set_id(&mut self, id: Option<u64>) {
+                            self.id = id;
+                        }
+
+                        #[allow(unused_variables)]
+                        fn set_from_str(
+                            &mut self, key: &str, value: &str,
+                        ) -> Result<bool, &'static str> {
+                            match key {
+                                #{for field in fields {
+                                    point_from_str(s, field);
+                                }}
+                                _ => Ok(false),
+                            }
+                        }
+
+                        fn values(&self) -> Vec<#ci::Value> {
+                            vec![#{for Field { key, .. } in fields {
+                                let ptv = format_ident!("encode_{key}");
+                                quote_into!(s += self.#ptv(),);
+                            }}]
+                        }
+
+                        #[allow(unused_mut, unused_variables)]
+                        fn for_each_value(&self, mut f: impl FnMut(#ci::ValueRef<'_>)) {
+                            #{for field in fields {
+                                point_value_ref(s, field);
+                            }}
+                        }
+
+                        fn decode_point(
+                            zom: u8, tx: u32, ty: u32,
+                            feature: &#ci::Feature, values: &[#ci::Value],
+                        ) -> Result<Self, &'static str> {
+                            #ident::decode_point(zom, tx, ty, feature, values)
+                        }
+                    }
                 }
             }
         }
@@ -193,6 +304,13 @@ pub fn mapack(code: TokenStream) -> TokenStream {
         }
 
         impl Tile {
+            /// name, fields and zoom range of every layer
+            pub const VECTOR_LAYERS: &'static [#ci::tilejson::VectorLayer] = &[
+                #{for layer in tile.layers.iter() {
+                    vector_layer(s, layer);
+                }}
+            ];
+
             pub fn new() -> Self {
                 Self {#{
                     for Layer { name, .. } in tile.layers.iter() {
@@ -201,77 +319,158 @@ pub fn mapack(code: TokenStream) -> TokenStream {
                 }}
             }
 
+            /// gzip and zstd compressed tiles are decompressed first, up to
+            /// [`MAX_DECOMPRESSED`](mapack::MAX_DECOMPRESSED) bytes. features
+            /// that are not a single point inside of the tile are skipped,
+            /// buffer points outside of `0..=4096` included
             #[allow(dead_code)]
-            pub fn decode(zom: u8, tx: u32, ty: u32, pbf: Vec<u8>) -> #ci::protobuf::Result<Self> {
+            pub fn decode(zom: u8, tx: u32, ty: u32, pbf: Vec<u8>) -> Result<Self, #ci::Error> {
+                Self::decode_skipped(zom, tx, ty, pbf).map(|(tile, _)| tile)
+            }
+
+            /// same as `decode`, also returns the number of skipped features
+            #[allow(dead_code)]
+            pub fn decode_skipped(zom: u8, tx: u32, ty: u32, pbf: Vec<u8>) -> Result<(Self, usize), #ci::Error> {
                 let mut tile = Self::new();
-                let vec_tile = <#ci::Tile as #ci::protobuf::Message>::parse_from_bytes(&pbf)?;
-                if vec_tile.layers.is_empty() { return Ok(tile); }
+                let mut skipped = 0;
+                let pbf = #ci::decompress(pbf)?;
+                let vec_tile = #ci::Tile::decode(&pbf)?;
 
                 for layer in vec_tile.layers.iter() {#{
                     tile_decode(s, &tile.layers)
                 }}
 
-                Ok(tile)
+                Ok((tile, skipped))
             }
 
             #[allow(dead_code)]
-            pub fn encode(&self) -> #ci::protobuf::Result<Vec<u8>> {
-                let mut vec_tile = #ci::Tile::default();
+            pub fn encode(&self) -> Vec<u8> {
+                let mut buf = Vec::new();
+                self.encode_to(&mut buf).expect("writing to a Vec does not fail");
+                buf
+            }
 
-                #{for layer in tile.layers.iter() {
-                    quote_into!(s += 'a: {#{tile_encode(s, layer)}});
+            /// Streams the encoded tile into `w`, layer by layer and without
+            /// cloning any string. see [`write_layer`](#ci::write_layer).
+            #[allow(dead_code)]
+            pub fn encode_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    quote_into!(s += #ci::write_layer(&mut w, self.#name.iter().map(|p| (p, &p.coordinate)))?;);
                 }}
+                Ok(())
+            }
+
+            #[allow(dead_code)]
+            pub fn encode_compressed(&self, compression: #ci::Compression) -> std::io::Result<Vec<u8>> {
+                #ci::compress(self.encode(), compression)
+            }
+
+            /// A TileJSON 3.0 document of this schema
+            #[allow(dead_code)]
+            pub fn tilejson(tilejson: &#ci::tilejson::TileJson) -> #ci::serde_json::Value {
+                tilejson.to_json(Self::VECTOR_LAYERS)
+            }
 
-                #ci::protobuf::Message::write_to_bytes(&vec_tile)
+            /// A FeatureCollection for every layer keyed by the layer name
+            #[allow(dead_code)]
+            pub fn to_geojson(&self) -> #ci::serde_json::Value {
+                let mut layers = #ci::serde_json::Map::new();
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    let name_str = name.to_string();
+                    quote_into!(s += layers.insert(
+                        String::from(#name_str),
+                        #ci::geojson::layer_to_geojson(&self.#name),
+                    ););
+                }}
+                #ci::serde_json::Value::Object(layers)
             }
         }
-    }
 
-    s.into()
-}
+        impl #ci::PointTile for Tile {
+            fn new() -> Self {
+                Tile::new()
+            }
 
-fn tile_encode(s: &mut TokenStream2, Layer { ident, name, fields }: &Layer) {
-    let keys_len = fields.len();
-    let ci = crate_ident();
-    let name_str = name.to_string();
+            fn decode(zom: u8, tx: u32, ty: u32, pbf: Vec<u8>) -> Result<Self, #ci::Error> {
+                Tile::decode(zom, tx, ty, pbf)
+            }
 
-    quote_into! {s +=
-        let mut values = Vec::<#ci::Value>::with_capacity(self.#name.len() * #keys_len);
-        let mut features = Vec::<#ci::Feature>::with_capacity(self.#name.len());
+            fn encode(&self) -> Vec<u8> {
+                Tile::encode(self)
+            }
 
-        for point in self.#name.iter() {
-            #{for Field { key, .. } in fields.iter() {
-                let ptv = format_ident!("encode_{key}");
-                let val = format_ident!("{key}_value");
-                quote_into! {s +=
-                    let #val = values.len() as u32;
-                    values.push(point.#ptv());
-                }
-            }}
+            fn encode_to<W: std::io::Write>(&self, w: W) -> std::io::Result<()> {
+                Tile::encode_to(self, w)
+            }
 
-            features.push(#ci::Feature {
-                id: point.id,
-                tags: vec![#{for (idx, Field { key, .. }) in fields.iter().enumerate() {
-                    let idx = idx as u32;
-                    let val = format_ident!("{key}_value");
-                    quote_into!(s += #idx, #val,);
-                }}],
-                geometry: point.coordinate.to_geometry().to_vec(),
-                type_: Some(#ci::protobuf::EnumOrUnknown::new(#ci::GeomType::POINT)),
-                ..Default::default()
-            });
-        }
+            fn retain_mut(&mut self, mut f: impl FnMut(&mut #ci::Coordinate) -> bool) {
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    quote_into!(s += self.#name.retain_mut(|p| f(&mut p.coordinate)););
+                }}
+            }
+
+            fn append(&mut self, other: &mut Self) {
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    quote_into!(s += self.#name.append(&mut other.#name););
+                }}
+            }
 
-        vec_tile.layers.push(#ci::Layer {
-            name: Some(String::from(#name_str)),
-            extent: Some(4096),
-            version: Some(2),
-            features,
-            keys: #ident::KEYS.map(|k| k.to_string()).to_vec(),
-            values,
-            ..Default::default()
-        });
+            fn interleave(&mut self, others: &mut [Self]) {
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    quote_into!(s += {
+                        let mut layers = others
+                            .iter_mut()
+                            .map(|o| std::mem::take(&mut o.#name).into_iter())
+                            .collect::<Vec<_>>();
+                        let mut more = true;
+                        while more {
+                            more = false;
+                            for layer in layers.iter_mut() {
+                                if let Some(p) = layer.next() {
+                                    self.#name.push(p);
+                                    more = true;
+                                }
+                            }
+                        }
+                    });
+                }}
+            }
+
+            fn apply_limit(&mut self, limit: &#ci::Limit) {
+                #{for Layer { name, .. } in tile.layers.iter() {
+                    quote_into!(s += limit.apply(&mut self.#name););
+                }}
+            }
+        }
     }
+
+    s.into()
+}
+
+fn vector_layer(s: &mut TokenStream2, layer: &Layer) {
+    let ci = crate_ident();
+    let name_str = layer.name.to_string();
+    let zoom = |z: Option<u8>| match z {
+        Some(z) => quote::quote!(Some(#z)),
+        None => quote::quote!(None),
+    };
+    let minzoom = zoom(layer.zoom.map(|z| z.0));
+    let maxzoom = zoom(layer.zoom.map(|z| z.1));
+
+    quote_into! {s += #ci::tilejson::VectorLayer {
+        id: #name_str,
+        fields: &[#{for Field { key, ty, .. } in layer.fields.iter() {
+            let ty = match ty.to_token_stream().to_string().as_str() {
+                "bool" => "Boolean",
+                "u8" | "u16" | "u32" | "u64" | "i8" | "i16" | "i32" | "i64"
+                | "f32" | "f64" => "Number",
+                _ => "String",
+            };
+            quote_into!(s += (#key, #ty),);
+        }}],
+        minzoom: #minzoom,
+        maxzoom: #maxzoom,
+    },}
 }
 
 fn tile_decode(s: &mut TokenStream2, layers: &[Layer]) {
@@ -282,7 +481,9 @@ fn tile_decode(s: &mut TokenStream2, layers: &[Layer]) {
             #{for Layer { name, ident, .. } in layers {
                 let name_str = name.to_string();
                 quote_into! {s += #name_str => {
-                    tile.#name = #ident::decode_layer(zom, tx, ty, layer)?;
+                    let (points, n) = #ident::decode_layer(zom, tx, ty, layer)?;
+                    tile.#name = points;
+                    skipped += n;
                 }}
             }}
             _ => {}
@@ -319,6 +520,30 @@ fn point_auto_encode(s: &mut TokenStream2, field: &Field) {
     }
 }
 
+fn point_value_ref(s: &mut TokenStream2, field: &Field) {
+    let ci = crate_ident();
+    let Field { ident, ty, key, .. } = field;
+
+    let ty_str = ty.to_token_stream().to_string();
+    match ty_str.as_str() {
+        _ if !field.auto_encode => {}
+        "bool" => return quote_into!(s += f(#ci::ValueRef::Bool(self.#ident));),
+        "u8" | "u16" | "u32" | "u64" => {
+            return quote_into!(s += f(#ci::ValueRef::Uint(self.#ident as u64));)
+        }
+        "i8" | "i16" | "i32" | "i64" => {
+            return quote_into!(s += f(#ci::ValueRef::Int(self.#ident as i64));)
+        }
+        "String" => {
+            return quote_into!(s += f(#ci::ValueRef::String(&self.#ident));)
+        }
+        _ => {}
+    }
+
+    let ptv = format_ident!("encode_{key}");
+    quote_into!(s += f((&self.#ptv()).into()););
+}
+
 fn point_auto_decode(s: &mut TokenStream2, field: &Field) {
     let ty = &field.ty;
     let ty_str = ty.to_token_stream().to_string();
@@ -341,13 +566,63 @@ fn point_auto_decode(s: &mut TokenStream2, field: &Field) {
     }
 }
 
-fn point_decode(s: &mut TokenStream2, Layer { fields, .. }: &Layer) {
+fn point_geojson_decode(s: &mut TokenStream2, field: &Field) {
     let ci = crate_ident();
+    let Field { ident, ty, key, .. } = field;
+    let ty_name = ty.segments.last().unwrap().ident.to_string();
+
+    let from_json = matches!(
+        ty.to_token_stream().to_string().as_str(),
+        "bool"
+            | "String"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+    );
 
     quote_into! {s +=
-        if feature.geometry.len() != 3 {
-            return Err("bad geometry");
+        let v = &properties[#key];
+        if !v.is_null() {
+            let value = #{if field.auto_decode && from_json {
+                quote_into!(s += <#ty as #ci::geojson::FromJson>::from_json(v));
+            } else {
+                let pfv = format_ident!("decode_{key}");
+                quote_into!(s += Self::#pfv(&#ci::geojson::json_to_value(v)));
+            }};
+            point.#ident = value.ok_or(#ci::geojson::GeoJsonError::BadProperty {
+                key: #key,
+                expected: #ty_name,
+            })?;
         }
+    }
+}
+
+fn point_from_str(s: &mut TokenStream2, field: &Field) {
+    let ci = crate_ident();
+    let Field { ident, ty, key, .. } = field;
+    let ty_name = ty.segments.last().unwrap().ident.to_string();
+
+    quote_into! {s += #key => {
+        let value = #{if field.auto_decode {
+            quote_into!(s += value.parse::<#ty>().ok());
+        } else {
+            let pfv = format_ident!("decode_{key}");
+            quote_into!(s += Self::#pfv(&#ci::Value::from_string(value.to_string())));
+        }};
+        self.#ident = value.ok_or(#ty_name)?;
+        Ok(true)
+    }}
+}
+
+fn point_decode(s: &mut TokenStream2, Layer { fields, .. }: &Layer) {
+    let ci = crate_ident();
+
+    quote_into! {s +=
 
         let tags = &feature.tags;
         // if tags.is_empty() {
@@ -357,8 +632,10 @@ fn point_decode(s: &mut TokenStream2, Layer { fields, .. }: &Layer) {
             return Err("bad tags length");
         }
 
-        let geometry: [u32; 3] = feature.geometry.clone().try_into().unwrap();
-        let mut point = Self::new(#ci::Coordinate::from_geometry(zom, tx, ty, geometry));
+        let Some(coordinate) = #ci::Coordinate::try_from_geometry(zom, tx, ty, &feature.geometry) else {
+            return Err("bad geometry");
+        };
+        let mut point = Self::new(coordinate);
         point.id = feature.id;
 
         let mut tags_iter = tags.iter();
@@ -383,7 +660,7 @@ fn point_decode(s: &mut TokenStream2, Layer { fields, .. }: &Layer) {
                         }
                     }}
                 }}
-                _ => unreachable!()
+                _ => return Err("invalid tags"),
             }
         }
 
@@ -0,0 +1,166 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{BatchSize, Throughput};
+use mapack::{Coordinate, LayerBuilder, Point, SpatialIndex, Tile};
+use mapack_fixtures::Rng;
+use std::hint::black_box;
+
+mod bench_tiles {
+    mapack::mapack! {
+        eatery: {
+            name: String,
+            category: u8,
+            rank: u32,
+            is_private: bool,
+        },
+    }
+}
+
+use bench_tiles::PointEatery;
+
+const ZOM: u8 = 14;
+const TX: u32 = 10680;
+const TY: u32 = 6499;
+const NAMES: [&str; 8] = [
+    "kebab house",
+    "cafe",
+    "bakery",
+    "pizza",
+    "tea house",
+    "burger",
+    "sushi",
+    "juice bar",
+];
+
+fn fixture(n: usize) -> bench_tiles::Tile {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut tile = bench_tiles::Tile::new();
+    for idx in 0..n {
+        let (sx, sy) = (rng.next_u32() % 4096, rng.next_u32() % 4096);
+        let c = Coordinate::from_screen(ZOM, TX, TY, sx, sy);
+        let mut point = PointEatery::new(c);
+        point.id = Some(idx as u64);
+        point.name = NAMES[rng.next_u32() as usize % NAMES.len()].to_string();
+        point.category = (rng.next_u32() % 16) as u8;
+        point.rank = rng.next_u32() % 100;
+        point.is_private = rng.next_u32().is_multiple_of(2);
+        tile.eatery.push(point);
+    }
+    tile
+}
+
+fn coordinate(c: &mut Criterion) {
+    let mut rng = Rng(42);
+    let latlng = (0..1024)
+        .map(|_| {
+            let lat = (rng.next_u32() % 160_000) as f64 / 1000.0 - 80.0;
+            let lng = (rng.next_u32() % 360_000) as f64 / 1000.0 - 180.0;
+            (lat, lng)
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("coordinate");
+    group.throughput(Throughput::Elements(latlng.len() as u64));
+    group.bench_function("from_latlng", |b| {
+        b.iter(|| {
+            for (lat, lng) in latlng.iter() {
+                black_box(Coordinate::from_latlng(ZOM, *lat, *lng));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn encode_decode(c: &mut Criterion) {
+    for n in [1_000, 100_000] {
+        let tile = fixture(n);
+        let pbf = tile.encode();
+
+        let mut group = c.benchmark_group("tile");
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("encode", n), |b| {
+            b.iter(|| black_box(tile.encode()))
+        });
+        group.bench_function(BenchmarkId::new("encode_to", n), |b| {
+            let mut buf = Vec::with_capacity(pbf.len());
+            b.iter(|| {
+                buf.clear();
+                tile.encode_to(&mut buf).unwrap();
+                black_box(buf.len())
+            })
+        });
+        group.bench_function(BenchmarkId::new("decode", n), |b| {
+            b.iter_batched(
+                || pbf.clone(),
+                |pbf| black_box(bench_tiles::Tile::decode(ZOM, TX, TY, pbf)),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("decode_raw", n), |b| {
+            b.iter(|| black_box(Tile::decode(&pbf)))
+        });
+        group.finish();
+    }
+}
+
+/// generated layers give every value its own entry, a [`LayerBuilder`]
+/// shares equal values
+fn dedup(c: &mut Criterion) {
+    let n = 100_000;
+    let tile = fixture(n);
+
+    let mut group = c.benchmark_group("dedup");
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_function("off", |b| b.iter(|| black_box(tile.encode())));
+    group.bench_function("on", |b| {
+        b.iter(|| {
+            let mut layer = LayerBuilder::new(PointEatery::NAME);
+            for point in tile.eatery.iter() {
+                let values = PointEatery::KEYS.iter().zip(point.values());
+                layer.push(point.id, &point.coordinate, values);
+            }
+            black_box(Tile { layers: vec![layer.build()] }.encode())
+        })
+    });
+    group.finish();
+}
+
+/// bucketing points by tile on every zoom, the first step of a pyramid
+fn index(c: &mut Criterion) {
+    let n = 100_000;
+    let mut rng = Rng(7);
+    let points = (0..n)
+        .map(|_| {
+            let lat = (rng.next_u32() % 160_000) as f64 / 1000.0 - 80.0;
+            let lng = (rng.next_u32() % 360_000) as f64 / 1000.0 - 180.0;
+            Coordinate::from_latlng(ZOM, lat, lng)
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("index");
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_function("build", |b| {
+        b.iter(|| black_box(SpatialIndex::new(&points).len()))
+    });
+    group.bench_function("tiles", |b| {
+        b.iter(|| {
+            let index = SpatialIndex::new(&points);
+            let tiles = (0..=ZOM).map(|zom| index.tiles(zom).count());
+            black_box(tiles.sum::<usize>())
+        })
+    });
+    group.bench_function("with_zoom", |b| {
+        b.iter(|| {
+            let mut tiles = std::collections::HashSet::new();
+            for zom in 0..=ZOM {
+                for c in points.iter() {
+                    tiles.insert((zom, c.with_zoom(zom).txy()));
+                }
+            }
+            black_box(tiles.len())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, coordinate, encode_decode, dedup, index);
+criterion_main!(benches);
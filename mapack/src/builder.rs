@@ -0,0 +1,133 @@
+use crate::{Coordinate, Feature, GeomType, Layer, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ValueKey {
+    String(String),
+    Float(u32),
+    Double(u64),
+    Int(i64),
+    Uint(u64),
+    Sint(i64),
+    Bool(bool),
+    Empty,
+}
+
+impl From<&Value> for ValueKey {
+    fn from(v: &Value) -> Self {
+        if let Some(s) = &v.string_value {
+            Self::String(s.clone())
+        } else if let Some(f) = v.float_value {
+            Self::Float(f.to_bits())
+        } else if let Some(d) = v.double_value {
+            Self::Double(d.to_bits())
+        } else if let Some(i) = v.int_value {
+            Self::Int(i)
+        } else if let Some(u) = v.uint_value {
+            Self::Uint(u)
+        } else if let Some(s) = v.sint_value {
+            Self::Sint(s)
+        } else if let Some(b) = v.bool_value {
+            Self::Bool(b)
+        } else {
+            Self::Empty
+        }
+    }
+}
+
+/// Builds a point [`Layer`] from features with arbitrary properties,
+/// keys and values are deduplicated.
+#[derive(Debug)]
+pub struct LayerBuilder {
+    layer: Layer,
+    keys: HashMap<String, u32>,
+    values: HashMap<ValueKey, u32>,
+}
+
+impl LayerBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            layer: Layer {
+                name: Some(name.to_string()),
+                extent: Some(4096),
+                version: Some(2),
+                ..Default::default()
+            },
+            keys: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// layers are version 2 with an extent of 4096 by default
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.layer.version = Some(version);
+        self
+    }
+
+    pub fn with_extent(mut self, extent: u32) -> Self {
+        self.layer.extent = Some(extent);
+        self
+    }
+
+    pub fn push<K: AsRef<str>>(
+        &mut self, id: Option<u64>, coordinate: &Coordinate,
+        properties: impl IntoIterator<Item = (K, Value)>,
+    ) {
+        let geometry = coordinate.to_geometry().to_vec();
+        self.push_geometry(id, GeomType::Point, geometry, properties);
+    }
+
+    /// push a feature with already encoded geometry commands
+    pub fn push_geometry<K: AsRef<str>>(
+        &mut self, id: Option<u64>, geom_type: GeomType, geometry: Vec<u32>,
+        properties: impl IntoIterator<Item = (K, Value)>,
+    ) {
+        let mut tags = Vec::<u32>::new();
+        for (key, value) in properties {
+            tags.push(self.key(key.as_ref()));
+            tags.push(self.value(value));
+        }
+
+        self.layer.features.push(Feature {
+            id,
+            tags,
+            geom_type: Some(geom_type),
+            geometry,
+        });
+    }
+
+    fn key(&mut self, key: &str) -> u32 {
+        if let Some(idx) = self.keys.get(key) {
+            return *idx;
+        }
+
+        let idx = self.layer.keys.len() as u32;
+        self.layer.keys.push(key.to_string());
+        self.keys.insert(key.to_string(), idx);
+        idx
+    }
+
+    fn value(&mut self, value: Value) -> u32 {
+        let vk = ValueKey::from(&value);
+        if let Some(idx) = self.values.get(&vk) {
+            return *idx;
+        }
+
+        let idx = self.layer.values.len() as u32;
+        self.layer.values.push(value);
+        self.values.insert(vk, idx);
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.layer.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layer.features.is_empty()
+    }
+
+    pub fn build(self) -> Layer {
+        self.layer
+    }
+}
@@ -0,0 +1,203 @@
+use crate::coordinate::MAX_ZOOM;
+use crate::{Coordinate, LayerBuilder, Point, Tile, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// A group of nearby points at a single zoom level.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    /// weighted center of the clustered points
+    pub coordinate: Coordinate,
+    /// indices of the clustered points in the input slice
+    pub points: Vec<usize>,
+}
+
+impl Cluster {
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_cluster(&self) -> bool {
+        self.points.len() > 1
+    }
+}
+
+/// Grid based hierarchical point clustering, in the style of supercluster.
+///
+/// Clusters of a zoom level are built by merging the clusters of the zoom
+/// level above it, starting from the plain points at `max_zoom`.
+#[derive(Debug, Clone)]
+pub struct Clusterer {
+    min_zoom: u8,
+    max_zoom: u8,
+    radius: u32,
+    min_points: usize,
+}
+
+impl Clusterer {
+    /// zoom levels are clamped to 22, the maximum zoom of [`Coordinate`]
+    pub fn new(min_zoom: u8, max_zoom: u8) -> Self {
+        let (min_zoom, max_zoom) =
+            (min_zoom.min(MAX_ZOOM), max_zoom.min(MAX_ZOOM));
+        Self { min_zoom, max_zoom, radius: 320, min_points: 2 }
+    }
+
+    /// cluster radius in tile extent units. a tile is 4096 units wide.
+    /// default is 320
+    pub fn with_radius(mut self, radius: u32) -> Self {
+        self.radius = radius.max(1);
+        self
+    }
+
+    /// minimum number of points needed to form a cluster. default is 2
+    pub fn with_min_points(mut self, min_points: usize) -> Self {
+        self.min_points = min_points.max(2);
+        self
+    }
+
+    /// clusters of every zoom level between `min_zoom` and `max_zoom`
+    pub fn clusters<P: Point>(
+        &self, points: &[P],
+    ) -> BTreeMap<u8, Vec<Cluster>> {
+        let mut out = BTreeMap::<u8, Vec<Cluster>>::new();
+        let mut level = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Cluster {
+                coordinate: p.coordinate().clone(),
+                points: vec![i],
+            })
+            .collect::<Vec<_>>();
+
+        for zom in (self.min_zoom..=self.max_zoom).rev() {
+            level = self.merge(zom, level);
+            out.insert(zom, level.clone());
+        }
+
+        out
+    }
+
+    fn merge(&self, zom: u8, level: Vec<Cluster>) -> Vec<Cluster> {
+        let r = self.radius as i64;
+        let pos = level
+            .iter()
+            .map(|c| {
+                let c = c.coordinate.with_zoom(zom);
+                let x = c.tx() as i64 * 4096 + c.sx() as i64;
+                let y = c.ty() as i64 * 4096 + 4096 - c.sy() as i64;
+                (x, y)
+            })
+            .collect::<Vec<_>>();
+
+        let mut grid = HashMap::<(i64, i64), Vec<usize>>::new();
+        for (i, (x, y)) in pos.iter().enumerate() {
+            grid.entry((x / r, y / r)).or_default().push(i);
+        }
+
+        let mut level = level.into_iter().map(Some).collect::<Vec<_>>();
+        let mut out = Vec::<Cluster>::with_capacity(level.len());
+
+        for i in 0..level.len() {
+            let Some(cluster) = level[i].take() else { continue };
+            let (x, y) = pos[i];
+
+            let mut near = Vec::<usize>::new();
+            for gx in x / r - 1..=x / r + 1 {
+                for gy in y / r - 1..=y / r + 1 {
+                    let Some(cell) = grid.get(&(gx, gy)) else { continue };
+                    for &j in cell {
+                        let (dx, dy) = (pos[j].0 - x, pos[j].1 - y);
+                        if level[j].is_some() && dx * dx + dy * dy <= r * r {
+                            near.push(j);
+                        }
+                    }
+                }
+            }
+
+            let count = near
+                .iter()
+                .filter_map(|&j| level[j].as_ref())
+                .fold(cluster.points.len(), |n, c| n + c.points.len());
+            if near.is_empty() || count < self.min_points {
+                out.push(cluster);
+                continue;
+            }
+
+            let (lat, lng) = cluster.coordinate.latlng();
+            let n = cluster.points.len() as f64;
+            let (mut lat, mut lng) = (lat * n, lng * n);
+            let mut points = cluster.points;
+
+            for j in near {
+                let Some(other) = level[j].take() else { continue };
+                let n = other.points.len() as f64;
+                lat += other.coordinate.latitude() * n;
+                lng += other.coordinate.longitude() * n;
+                points.extend(other.points);
+            }
+
+            let n = points.len() as f64;
+            out.push(Cluster {
+                coordinate: Coordinate::from_latlng(zom, lat / n, lng / n),
+                points,
+            });
+        }
+
+        for cluster in out.iter_mut() {
+            cluster.coordinate.set_zoom(zom);
+        }
+
+        out
+    }
+
+    /// Builds a tile for every non-empty z/x/y between `min_zoom` and
+    /// `max_zoom` with a single layer named `P::NAME`.
+    ///
+    /// Unclustered points keep their id and values. clusters get
+    /// `cluster: true`, `point_count` and what ever `aggregate` returns
+    /// for the clustered points.
+    pub fn tiles<P, F>(
+        &self, points: &[P], aggregate: F,
+    ) -> BTreeMap<(u8, u32, u32), Tile>
+    where
+        P: Point,
+        F: Fn(&[&P]) -> Vec<(String, Value)>,
+    {
+        let mut layers = BTreeMap::<(u8, u32, u32), LayerBuilder>::new();
+
+        for (zom, clusters) in self.clusters(points) {
+            for cluster in clusters {
+                let (tx, ty) = cluster.coordinate.txy();
+                let layer = layers
+                    .entry((zom, tx, ty))
+                    .or_insert_with(|| LayerBuilder::new(P::NAME));
+
+                if let [idx] = cluster.points[..] {
+                    let point = &points[idx];
+                    let values = P::KEYS.iter().zip(point.values());
+                    layer.push(point.id(), &cluster.coordinate, values);
+                    continue;
+                }
+
+                let members = cluster
+                    .points
+                    .iter()
+                    .map(|&i| &points[i])
+                    .collect::<Vec<_>>();
+                let mut props = vec![
+                    (String::from("cluster"), Value::from_bool(true)),
+                    (
+                        String::from("point_count"),
+                        Value::from_uint(members.len() as u64),
+                    ),
+                ];
+                props.extend(aggregate(&members));
+                layer.push(None, &cluster.coordinate, props);
+            }
+        }
+
+        layers
+            .into_iter()
+            .map(|(k, layer)| (k, Tile { layers: vec![layer.build()] }))
+            .collect()
+    }
+}
@@ -0,0 +1,162 @@
+use std::io;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::io::Read;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Largest tile [`decompress`] inflates to, 64 MiB. guards against small
+/// compressed uploads that inflate to gigabytes
+pub const MAX_DECOMPRESSED: usize = 64 << 20;
+
+/// Compression of an encoded tile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// what MBTiles and most CDNs expect
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// detected from the magic bytes at the start of `data`
+    pub fn detect(data: &[u8]) -> io::Result<Self> {
+        match content_encoding(data) {
+            #[cfg(feature = "gzip")]
+            Some("gzip") => Ok(Self::Gzip),
+            #[cfg(feature = "zstd")]
+            Some("zstd") => Ok(Self::Zstd),
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            Some(name) => Err(disabled(name)),
+            _ => Ok(Self::None),
+        }
+    }
+}
+
+/// HTTP content encoding of compressed `data`, also with the feature of
+/// that compression disabled
+pub(crate) fn content_encoding(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&GZIP_MAGIC) {
+        Some("gzip")
+    } else if data.starts_with(&ZSTD_MAGIC) {
+        Some("zstd")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn disabled(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("tile is {name} compressed but the {name} feature is disabled"),
+    )
+}
+
+pub fn compress(
+    data: Vec<u8>, compression: Compression,
+) -> io::Result<Vec<u8>> {
+    match compression {
+        Compression::None => Ok(data),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(
+                Vec::with_capacity(data.len() / 2),
+                flate2::Compression::default(),
+            );
+            io::Write::write_all(&mut encoder, &data)?;
+            encoder.finish()
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::encode_all(&data[..], 0),
+    }
+}
+
+/// Decompresses gzip and zstd tiles, anything else is returned as it is.
+/// fails for tiles inflating past [`MAX_DECOMPRESSED`]
+pub fn decompress(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    decompress_with_limit(data, MAX_DECOMPRESSED)
+}
+
+/// Same as [`decompress`], failing past `limit` bytes instead.
+#[allow(unused_variables)]
+pub fn decompress_with_limit(
+    data: Vec<u8>, limit: usize,
+) -> io::Result<Vec<u8>> {
+    match Compression::detect(&data)? {
+        Compression::None => Ok(data),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let decoder = flate2::read::GzDecoder::new(&data[..]);
+            read_limited(decoder, data.len(), limit)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let decoder = zstd::Decoder::new(&data[..])?;
+            read_limited(decoder, data.len(), limit)
+        }
+    }
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn read_limited(
+    decoder: impl Read, len: usize, limit: usize,
+) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(len.saturating_mul(4).min(limit));
+    decoder.take(limit as u64 + 1).read_to_end(&mut out)?;
+    if out.len() > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompressed tile is over {limit} bytes"),
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress, decompress_with_limit, Compression};
+
+    fn enabled() -> Vec<Compression> {
+        #[allow(unused_mut)]
+        let mut all = vec![Compression::None];
+        #[cfg(feature = "gzip")]
+        all.push(Compression::Gzip);
+        #[cfg(feature = "zstd")]
+        all.push(Compression::Zstd);
+        all
+    }
+
+    #[test]
+    fn roundtrip() {
+        let data = [0x1a, 0x04].repeat(100);
+        assert_eq!(Compression::detect(&data).unwrap(), Compression::None);
+
+        for c in enabled() {
+            let packed = compress(data.clone(), c).unwrap();
+            assert_eq!(Compression::detect(&packed).unwrap(), c);
+            assert_eq!(decompress(packed).unwrap(), data);
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        assert!(decompress(vec![0x28, 0xb5, 0x2f, 0xfd, 0]).is_err());
+    }
+
+    #[test]
+    fn bomb() {
+        let size = 1 << 20;
+        for c in enabled().into_iter().skip(1) {
+            let bomb = compress(vec![0; size], c).unwrap();
+            assert!(bomb.len() < 4096);
+
+            let e = decompress_with_limit(bomb.clone(), 1000).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+            let e = decompress_with_limit(bomb.clone(), size - 1).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+            assert_eq!(decompress_with_limit(bomb, size).unwrap().len(), size);
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use crate::geometry::{unzigzag, zigzag};
 use core::f64::consts::PI;
 
-const MAX_ZOOM: u8 = 22;
+pub(crate) const MAX_ZOOM: u8 = 22;
+/// radius of the earth used by Web Mercator and [`Coordinate::distance_to`]
+pub(crate) const EARTH_RADIUS: f64 = 6378137.0;
+/// half the width of the map in Web Mercator (EPSG:3857) meters
+pub(crate) const MERCATOR_EXTENT: f64 = PI * EARTH_RADIUS;
 
 #[derive(Debug, Clone)]
 pub struct Coordinate {
@@ -30,6 +35,28 @@ impl Coordinate {
         coords
     }
 
+    /// From Web Mercator (EPSG:3857) meters, clamped to the edge of the
+    /// map. the position on the tile is taken from the meters directly
+    pub fn from_meters(zoom: u8, x: f64, y: f64) -> Self {
+        let x = x.clamp(-MERCATOR_EXTENT, MERCATOR_EXTENT);
+        let y = y.clamp(-MERCATOR_EXTENT, MERCATOR_EXTENT);
+        let lng = x / MERCATOR_EXTENT * 180.0;
+        let lat = (y / EARTH_RADIUS).sinh().atan().to_degrees();
+
+        let world = (
+            (x + MERCATOR_EXTENT) / (2.0 * MERCATOR_EXTENT),
+            (MERCATOR_EXTENT - y) / (2.0 * MERCATOR_EXTENT),
+        );
+        Self::from_world(zoom, (lat, lng), world)
+    }
+
+    /// Web Mercator (EPSG:3857) x and y in meters
+    pub fn meters(&self) -> (f64, f64) {
+        let x = self.lng / 180.0 * MERCATOR_EXTENT;
+        let y = self.lat.to_radians().tan().asinh() * EARTH_RADIUS;
+        (x, y)
+    }
+
     pub fn set_zoom(&mut self, zoom: u8) {
         self.zom = zoom.clamp(0, MAX_ZOOM);
         self.update_index();
@@ -41,10 +68,25 @@ impl Coordinate {
         new
     }
 
+    /// Builds a coordinate from its position on the whole map, see
+    /// [`world`]. skips the projection of [`Coordinate::from_latlng`]
+    pub(crate) fn from_world(
+        zom: u8, (lat, lng): (f64, f64), (x, y): (f64, f64),
+    ) -> Self {
+        let zom = zom.clamp(0, MAX_ZOOM);
+        let mut coords = Self { zom, lat, lng, sx: 0, sy: 0, tx: 0, ty: 0 };
+        coords.update_world(x, y);
+        coords
+    }
+
     fn update_index(&mut self) {
+        let (x, y) = world(self.lat, self.lng);
+        self.update_world(x, y);
+    }
+
+    fn update_world(&mut self, x: f64, y: f64) {
         let n = (1 << self.zom) as f64;
-        let tx = (self.lng + 180.0) / 360.0 * n;
-        let ty = (1.0 - self.lat.to_radians().tan().asinh() / PI) / 2.0 * n;
+        let (tx, ty) = (x * n, y * n);
 
         self.sx = ((tx % 1.0) * 4096.0) as u32;
         self.sy = 4096 - ((ty % 1.0) * 4096.0) as u32;
@@ -70,23 +112,34 @@ impl Coordinate {
         coords
     }
 
+    /// positions outside of the tile are clamped to its edge, see
+    /// [`Coordinate::try_from_geometry`]
     pub fn from_geometry(zom: u8, tx: u32, ty: u32, geom: [u32; 3]) -> Self {
-        let sx = geom[1] as i32;
-        let sy = geom[2] as i32;
-        let sx = (sx >> 1) ^ (-(sx & 1));
-        let sy = (sy >> 1) ^ (-(sy & 1));
-        let sy = 4096 - sy;
+        let sx = unzigzag(geom[1]).clamp(0, 4096);
+        let sy = 4096 - unzigzag(geom[2]).clamp(0, 4096);
 
         Self::from_screen(zom, tx, ty, sx as u32, sy as u32)
     }
 
+    /// `None` unless `geom` is a single `MoveTo` inside of the tile
+    pub fn try_from_geometry(
+        zom: u8, tx: u32, ty: u32, geom: &[u32],
+    ) -> Option<Self> {
+        let &[cmd, x, y] = geom else { return None };
+        let inside = |v: i64| (0..=4096).contains(&v);
+        if cmd != 9 || !inside(unzigzag(x)) || !inside(unzigzag(y)) {
+            return None;
+        }
+
+        Some(Self::from_geometry(zom, tx, ty, [cmd, x, y]))
+    }
+
     pub fn to_geometry(&self) -> [u32; 3] {
         let cmd = (1u32 & 0x7) | (1 << 3);
-        let x = (self.sx << 1) ^ (self.sx >> 31);
-        let y = 4096 - self.sy;
-        let y = (y << 1) ^ (y >> 31);
+        let x = self.sx as i64;
+        let y = 4096 - self.sy as i64;
 
-        [cmd, x, y]
+        [cmd, zigzag(x), zigzag(y)]
     }
 
     /// Implementation of Haversine distance between two points. in meters
@@ -102,7 +155,7 @@ impl Coordinate {
         let hav_delta_lam = phi1.cos() * phi2.cos() * haversine_fn(lam2 - lam1);
         let total_delta = hav_delta_phi + hav_delta_lam;
 
-        (2.0 * 6378137.0 * total_delta.sqrt().asin() * 1e3).round() / 1e3
+        (2.0 * EARTH_RADIUS * total_delta.sqrt().asin() * 1e3).round() / 1e3
     }
 
     pub fn zoom(&self) -> u8 {
@@ -146,6 +199,14 @@ impl Coordinate {
     }
 }
 
+/// position of a latitude and longitude on the map at zoom 0, from 0.0
+/// to 1.0 with y growing to the south
+pub(crate) fn world(lat: f64, lng: f64) -> (f64, f64) {
+    let x = (lng + 180.0) / 360.0;
+    let y = (1.0 - lat.to_radians().tan().asinh() / PI) / 2.0;
+    (x, y)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Coordinate;
@@ -185,4 +246,44 @@ mod tests {
             assert_eq!(gs.lng, fs.lng);
         }
     }
+
+    #[test]
+    fn meters() {
+        let fl =
+            Coordinate::from_latlng(16, 35.55280736473704, 50.38718834763207);
+        let (x, y) = fl.meters();
+        assert!((x - 5609076.149).abs() < 0.001, "{x}");
+        assert!((y - 4239261.544).abs() < 0.001, "{y}");
+
+        let fm = Coordinate::from_meters(16, x, y);
+        assert_eq!(fm.txy(), fl.txy());
+        assert!(fm.sx.abs_diff(fl.sx) <= 1 && fm.sy.abs_diff(fl.sy) <= 1);
+        assert!(fm.distance_to(&fl) < 0.001);
+
+        let corner = Coordinate::from_meters(0, -1e9, 1e9);
+        assert_eq!((corner.txy(), corner.sxy()), ((0, 0), (0, 4096)));
+        assert!((corner.longitude() + 180.0).abs() < 1e-9);
+        assert!((corner.latitude() - 85.0511287798).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hostile_geometry() {
+        let c = Coordinate::from_geometry(12, 2621, 1614, [9, u32::MAX, 1]);
+        assert_eq!(c.sxy(), (0, 4096));
+        let c = Coordinate::from_geometry(12, 2621, 1614, [9, 0xfffffffe, 0]);
+        assert_eq!(c.sxy(), (4096, 4096));
+
+        let c = Coordinate::from_screen(12, 2621, 1614, u32::MAX, u32::MAX);
+        c.to_geometry();
+
+        let ok = Coordinate::from_geometry(12, 2621, 1614, [9, 2, 4]);
+        let geometry = ok.to_geometry();
+        let tried = Coordinate::try_from_geometry(12, 2621, 1614, &geometry);
+        assert_eq!(tried.map(|c| c.sxy()), Some(ok.sxy()));
+        for bad in [&[9, 8194, 0][..], &[9, 1, 0], &[10, 0, 0], &[9, 0]] {
+            assert!(
+                Coordinate::try_from_geometry(12, 2621, 1614, bad).is_none()
+            );
+        }
+    }
 }
@@ -0,0 +1,194 @@
+use crate::{Coordinate, Point};
+use std::fmt;
+use std::io;
+
+/// Why a single row of a csv file was skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowError {
+    /// the row itself could not be read
+    Csv(String),
+    /// not a number between -90 and 90
+    BadLatitude(String),
+    /// not a number between -180 and 180
+    BadLongitude(String),
+    BadId(String),
+    /// column `key` could not be parsed into `expected`
+    BadField {
+        key: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv(e) => write!(f, "{e}"),
+            Self::BadLatitude(v) => write!(f, "bad latitude: {v:?}"),
+            Self::BadLongitude(v) => write!(f, "bad longitude: {v:?}"),
+            Self::BadId(v) => write!(f, "bad id: {v:?}"),
+            Self::BadField { key, expected } => {
+                write!(f, "column {key} is not a valid {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RowError {}
+
+/// Points read from a csv file and the rows that were skipped.
+#[derive(Debug)]
+pub struct CsvReport<P> {
+    pub points: Vec<P>,
+    /// line number and error of every skipped row
+    pub errors: Vec<(u64, RowError)>,
+}
+
+/// Reads the rows of a csv file with a header into the points of a layer.
+///
+/// Columns are matched to the fields by their key, unknown columns and
+/// empty cells are ignored.
+#[derive(Debug, Clone)]
+pub struct CsvReader {
+    zoom: u8,
+    latitude: String,
+    longitude: String,
+    id: Option<String>,
+    delimiter: u8,
+}
+
+impl CsvReader {
+    /// reads the coordinates from the `latitude` and `longitude` columns
+    pub fn new(zoom: u8) -> Self {
+        Self {
+            zoom,
+            latitude: String::from("latitude"),
+            longitude: String::from("longitude"),
+            id: None,
+            delimiter: b',',
+        }
+    }
+
+    pub fn with_latlng_columns(
+        mut self, latitude: &str, longitude: &str,
+    ) -> Self {
+        self.latitude = latitude.to_string();
+        self.longitude = longitude.to_string();
+        self
+    }
+
+    /// column of the feature id
+    pub fn with_id_column(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Fails only if the header can not be read or is missing the
+    /// coordinate or id columns, bad rows end up in [`CsvReport::errors`].
+    pub fn read<P: Point, R: io::Read>(
+        &self, reader: R,
+    ) -> io::Result<CsvReport<P>> {
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .from_reader(reader);
+
+        let header = reader.headers().map_err(io::Error::other)?.clone();
+        let column = |name: &str| {
+            header.iter().position(|h| h == name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("missing column {name}"),
+                )
+            })
+        };
+        let columns = Columns {
+            lat: column(&self.latitude)?,
+            lng: column(&self.longitude)?,
+            id: self.id.as_deref().map(column).transpose()?,
+        };
+
+        let mut report = CsvReport { points: Vec::new(), errors: Vec::new() };
+        let mut record = ::csv::StringRecord::new();
+
+        loop {
+            match reader.read_record(&mut record) {
+                Ok(false) => break,
+                Ok(true) => {
+                    let line = record.position().map(|p| p.line()).unwrap_or(0);
+                    match self.row(&header, &columns, &record) {
+                        Ok(point) => report.points.push(point),
+                        Err(e) => report.errors.push((line, e)),
+                    }
+                }
+                Err(e) if e.is_io_error() => return Err(io::Error::other(e)),
+                Err(e) => {
+                    let line = e.position().map(|p| p.line()).unwrap_or(0);
+                    report.errors.push((line, RowError::Csv(e.to_string())));
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn row<P: Point>(
+        &self, header: &::csv::StringRecord, columns: &Columns,
+        record: &::csv::StringRecord,
+    ) -> Result<P, RowError> {
+        let cell = |idx: usize| record.get(idx).unwrap_or_default();
+
+        let lat = cell(columns.lat);
+        let lat = lat
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| (-90.0..=90.0).contains(v))
+            .ok_or_else(|| RowError::BadLatitude(lat.to_string()))?;
+        let lng = cell(columns.lng);
+        let lng = lng
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| (-180.0..=180.0).contains(v))
+            .ok_or_else(|| RowError::BadLongitude(lng.to_string()))?;
+
+        let mut point = P::new(Coordinate::from_latlng(self.zoom, lat, lng));
+
+        if let Some(idx) = columns.id {
+            let id = cell(idx).trim();
+            if !id.is_empty() {
+                let id = id
+                    .parse::<u64>()
+                    .map_err(|_| RowError::BadId(id.to_string()))?;
+                point.set_id(Some(id));
+            }
+        }
+
+        for (idx, key) in header.iter().enumerate() {
+            let value = cell(idx);
+            if value.is_empty()
+                || idx == columns.lat
+                || idx == columns.lng
+                || Some(idx) == columns.id
+            {
+                continue;
+            }
+
+            point.set_from_str(key, value).map_err(|expected| {
+                RowError::BadField { key: key.to_string(), expected }
+            })?;
+        }
+
+        Ok(point)
+    }
+}
+
+struct Columns {
+    lat: usize,
+    lng: usize,
+    id: Option<usize>,
+}
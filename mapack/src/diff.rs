@@ -0,0 +1,217 @@
+use crate::{
+    Coordinate, DynFeature, DynGeometry, DynLayer, DynTile, TileValueEnum,
+};
+use std::fmt;
+
+/// A semantic difference between two tiles, see [`diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        layer: String,
+        id: Option<u64>,
+    },
+    Removed {
+        layer: String,
+        id: Option<u64>,
+    },
+    /// geometry changed, `distance` in meters is only known for points
+    Moved {
+        layer: String,
+        id: u64,
+        distance: Option<f64>,
+    },
+    /// a property was added, removed or changed
+    Changed {
+        layer: String,
+        id: u64,
+        key: String,
+        old: Option<TileValueEnum>,
+        new: Option<TileValueEnum>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = |id: &Option<u64>| match id {
+            Some(id) => format!("#{id}"),
+            None => String::from("#-"),
+        };
+
+        match self {
+            Self::Added { layer, id: i } => write!(f, "+ {layer} {}", id(i)),
+            Self::Removed { layer, id: i } => write!(f, "- {layer} {}", id(i)),
+            Self::Moved { layer, id, distance: Some(d) } => {
+                write!(f, "~ {layer} #{id} moved {d:.2}m")
+            }
+            Self::Moved { layer, id, distance: None } => {
+                write!(f, "~ {layer} #{id} geometry changed")
+            }
+            Self::Changed { layer, id, key, old, new } => {
+                write!(f, "~ {layer} #{id} {key}: {old:?} -> {new:?}")
+            }
+        }
+    }
+}
+
+fn same_point(a: &Coordinate, b: &Coordinate) -> bool {
+    a.latlng() == b.latlng()
+}
+
+fn same_geometry(a: &DynGeometry, b: &DynGeometry) -> bool {
+    match (a, b) {
+        (DynGeometry::Point(a), DynGeometry::Point(b)) => same_point(a, b),
+        (DynGeometry::MultiPoint(a), DynGeometry::MultiPoint(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_point(a, b))
+        }
+        (
+            DynGeometry::Other { geom_type: at, commands: ac },
+            DynGeometry::Other { geom_type: bt, commands: bc },
+        ) => at == bt && ac == bc,
+        _ => false,
+    }
+}
+
+fn compare(
+    layer: &str, id: u64, a: &DynFeature, b: &DynFeature, out: &mut Vec<Change>,
+) {
+    if !same_geometry(&a.geometry, &b.geometry) {
+        let distance = match (&a.geometry, &b.geometry) {
+            (DynGeometry::Point(a), DynGeometry::Point(b)) => {
+                Some(a.distance_to(b))
+            }
+            _ => None,
+        };
+        out.push(Change::Moved { layer: layer.to_string(), id, distance });
+    }
+
+    let keys = a
+        .properties
+        .keys()
+        .chain(b.properties.keys().filter(|k| !a.properties.contains_key(*k)));
+    for key in keys {
+        let (old, new) = (a.properties.get(key), b.properties.get(key));
+        if old != new {
+            out.push(Change::Changed {
+                layer: layer.to_string(),
+                id,
+                key: key.clone(),
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+}
+
+fn diff_layer(
+    name: &str, a: &[DynFeature], b: &[DynFeature], out: &mut Vec<Change>,
+) {
+    let mut unmatched = b.iter().map(Some).collect::<Vec<_>>();
+
+    for fa in a {
+        let found = unmatched.iter_mut().find(|fb| match (fb, fa.id) {
+            (Some(fb), Some(id)) => fb.id == Some(id),
+            (Some(fb), None) => {
+                fb.id.is_none()
+                    && fb.properties == fa.properties
+                    && same_geometry(&fb.geometry, &fa.geometry)
+            }
+            (None, _) => false,
+        });
+
+        match (found.and_then(Option::take), fa.id) {
+            (Some(fb), Some(id)) => compare(name, id, fa, fb, out),
+            (Some(_), None) => {}
+            (None, id) => {
+                out.push(Change::Removed { layer: name.to_string(), id })
+            }
+        }
+    }
+
+    for fb in unmatched.into_iter().flatten() {
+        out.push(Change::Added { layer: name.to_string(), id: fb.id });
+    }
+}
+
+/// Semantic differences from tile `a` to tile `b`.
+///
+/// Layers are matched by name and features by id. features without an id
+/// only match an identical feature, so any change to them shows up as a
+/// removal and an addition.
+pub fn diff(a: &DynTile, b: &DynTile) -> Vec<Change> {
+    let mut out = Vec::new();
+    let empty = DynLayer::new("");
+
+    for la in a.layers.iter() {
+        let lb = b.layer(&la.name).unwrap_or(&empty);
+        diff_layer(&la.name, &la.features, &lb.features, &mut out);
+    }
+
+    for lb in b.layers.iter().filter(|l| a.layer(&l.name).is_none()) {
+        diff_layer(&lb.name, &[], &lb.features, &mut out);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change};
+    use crate::{Coordinate, DynFeature, DynGeometry, DynLayer, DynTile};
+
+    fn feature(id: Option<u64>, c: &Coordinate, name: &str) -> DynFeature {
+        DynFeature {
+            id,
+            geometry: DynGeometry::Point(c.clone()),
+            properties: [(String::from("name"), name.into())].into(),
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let c =
+            Coordinate::from_latlng(12, 35.55280736473704, 50.38718834763207);
+        let (tx, ty) = c.txy();
+        let (sx, sy) = c.sxy();
+        let moved = Coordinate::from_screen(12, tx, ty, sx + 10, sy);
+
+        let mut poi = DynLayer::new("poi");
+        poi.features.push(feature(Some(1), &c, "a"));
+        poi.features.push(feature(Some(2), &c, "b"));
+        poi.features.push(feature(Some(3), &c, "c"));
+        poi.features.push(feature(None, &c, "d"));
+        let a = DynTile { layers: vec![poi.clone()] };
+
+        poi.features.remove(1);
+        poi.features[0] = feature(Some(1), &moved, "a");
+        poi.features[1] = feature(Some(3), &c, "cc");
+        poi.features.push(feature(Some(4), &c, "e"));
+        let mut eatery = DynLayer::new("eatery");
+        eatery.features.push(feature(None, &c, "f"));
+        let b = DynTile { layers: vec![poi, eatery] };
+
+        assert_eq!(diff(&a, &a), vec![]);
+
+        let changes = diff(&a, &b);
+        let Change::Moved { id: 1, distance: Some(d), .. } = changes[0] else {
+            panic!("not moved")
+        };
+        assert!((d - moved.distance_to(&c)).abs() < 1e-9 && d > 0.0);
+
+        let layer = |name: &str| String::from(name);
+        assert_eq!(
+            changes[1..],
+            [
+                Change::Removed { layer: layer("poi"), id: Some(2) },
+                Change::Changed {
+                    layer: layer("poi"),
+                    id: 3,
+                    key: String::from("name"),
+                    old: Some("c".into()),
+                    new: Some("cc".into()),
+                },
+                Change::Added { layer: layer("poi"), id: Some(4) },
+                Change::Added { layer: layer("eatery"), id: None },
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use crate::geometry::encode_points;
+use crate::{
+    compress, decompress, Compression, Coordinate, Error, GeomType, Layer,
+    LayerBuilder, Tile, Value,
+};
+use std::collections::BTreeMap;
+use std::io;
+
+/// A single value of a [`Value`] message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileValueEnum {
+    String(String),
+    Float(f32),
+    Double(f64),
+    Int(i64),
+    Uint(u64),
+    Sint(i64),
+    Bool(bool),
+}
+
+impl TileValueEnum {
+    /// `None` for values without any field set
+    pub fn from_value(value: &Value) -> Option<Self> {
+        if let Some(v) = &value.string_value {
+            Some(Self::String(v.clone()))
+        } else if let Some(v) = value.float_value {
+            Some(Self::Float(v))
+        } else if let Some(v) = value.double_value {
+            Some(Self::Double(v))
+        } else if let Some(v) = value.int_value {
+            Some(Self::Int(v))
+        } else if let Some(v) = value.uint_value {
+            Some(Self::Uint(v))
+        } else if let Some(v) = value.sint_value {
+            Some(Self::Sint(v))
+        } else {
+            value.bool_value.map(Self::Bool)
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut value = Value::default();
+        match self {
+            Self::String(v) => value.string_value = Some(v.clone()),
+            Self::Float(v) => value.float_value = Some(*v),
+            Self::Double(v) => value.double_value = Some(*v),
+            Self::Int(v) => value.int_value = Some(*v),
+            Self::Uint(v) => value.uint_value = Some(*v),
+            Self::Sint(v) => value.sint_value = Some(*v),
+            Self::Bool(v) => value.bool_value = Some(*v),
+        }
+        value
+    }
+}
+
+impl From<String> for TileValueEnum {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+impl From<&str> for TileValueEnum {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_string())
+    }
+}
+
+impl From<f64> for TileValueEnum {
+    fn from(v: f64) -> Self {
+        Self::Double(v)
+    }
+}
+
+impl From<i64> for TileValueEnum {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<u64> for TileValueEnum {
+    fn from(v: u64) -> Self {
+        Self::Uint(v)
+    }
+}
+
+impl From<bool> for TileValueEnum {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+/// Geometry of a [`DynFeature`].
+#[derive(Debug, Clone)]
+pub enum DynGeometry {
+    Point(Coordinate),
+    MultiPoint(Vec<Coordinate>),
+    /// lines, polygons and points outside of the tile, kept as the raw
+    /// command integers
+    Other {
+        geom_type: GeomType,
+        commands: Vec<u32>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DynFeature {
+    pub id: Option<u64>,
+    pub geometry: DynGeometry,
+    pub properties: BTreeMap<String, TileValueEnum>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DynLayer {
+    pub name: String,
+    pub version: u32,
+    pub extent: u32,
+    pub features: Vec<DynFeature>,
+}
+
+impl DynLayer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: 2,
+            extent: 4096,
+            features: Vec::new(),
+        }
+    }
+
+    /// Invalid tags are skipped. point coordinates are scaled from the
+    /// extent of the layer to 4096.
+    pub fn from_layer(zom: u8, tx: u32, ty: u32, layer: &Layer) -> Self {
+        let features = layer
+            .features
+            .iter()
+            .map(|f| {
+                let geom_type = f.geom_type();
+                let geometry = match layer.points(zom, tx, ty, f) {
+                    Some(mut p) if p.len() == 1 => {
+                        DynGeometry::Point(p.remove(0))
+                    }
+                    Some(p) => DynGeometry::MultiPoint(p),
+                    None => DynGeometry::Other {
+                        geom_type,
+                        commands: f.geometry.clone(),
+                    },
+                };
+
+                let properties = f
+                    .tags
+                    .chunks_exact(2)
+                    .filter_map(|kv| {
+                        let key = layer.keys.get(kv[0] as usize)?;
+                        let value = layer.values.get(kv[1] as usize)?;
+                        Some((key.clone(), TileValueEnum::from_value(value)?))
+                    })
+                    .collect();
+
+                DynFeature { id: f.id, geometry, properties }
+            })
+            .collect();
+
+        Self {
+            name: layer.name().to_string(),
+            version: layer.version(),
+            extent: layer.extent(),
+            features,
+        }
+    }
+
+    /// keys and values are deduplicated
+    pub fn to_layer(&self) -> Layer {
+        let extent = self.extent as i64;
+        let position = |c: &Coordinate| {
+            let (sx, sy) = c.sxy();
+            (sx as i64 * extent / 4096, (4096 - sy as i64) * extent / 4096)
+        };
+
+        let mut layer = LayerBuilder::new(&self.name)
+            .with_version(self.version)
+            .with_extent(self.extent);
+
+        for f in self.features.iter() {
+            let (geom_type, commands) = match &f.geometry {
+                DynGeometry::Point(c) => {
+                    (GeomType::Point, encode_points(&[position(c)]))
+                }
+                DynGeometry::MultiPoint(p) => {
+                    let p = p.iter().map(position).collect::<Vec<_>>();
+                    (GeomType::Point, encode_points(&p))
+                }
+                DynGeometry::Other { geom_type, commands } => {
+                    (*geom_type, commands.clone())
+                }
+            };
+
+            let properties =
+                f.properties.iter().map(|(k, v)| (k, v.to_value()));
+            layer.push_geometry(f.id, geom_type, commands, properties);
+        }
+
+        layer.build()
+    }
+}
+
+/// A tile with its schema only known at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct DynTile {
+    pub layers: Vec<DynLayer>,
+}
+
+impl DynTile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// gzip and zstd compressed tiles are decompressed first, up to
+    /// [`MAX_DECOMPRESSED`](crate::MAX_DECOMPRESSED) bytes
+    pub fn decode(
+        zom: u8, tx: u32, ty: u32, pbf: Vec<u8>,
+    ) -> Result<Self, Error> {
+        let tile = Tile::decode(&decompress(pbf)?)?;
+        Ok(Self::from_tile(zom, tx, ty, &tile))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.to_tile().encode()
+    }
+
+    pub fn encode_compressed(
+        &self, compression: Compression,
+    ) -> io::Result<Vec<u8>> {
+        compress(self.encode(), compression)
+    }
+
+    pub fn from_tile(zom: u8, tx: u32, ty: u32, tile: &Tile) -> Self {
+        Self {
+            layers: tile
+                .layers
+                .iter()
+                .map(|l| DynLayer::from_layer(zom, tx, ty, l))
+                .collect(),
+        }
+    }
+
+    pub fn to_tile(&self) -> Tile {
+        Tile { layers: self.layers.iter().map(DynLayer::to_layer).collect() }
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&DynLayer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut DynLayer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynGeometry, DynLayer, DynTile, TileValueEnum};
+    use crate::{Coordinate, GeomType};
+
+    #[test]
+    fn roundtrip() {
+        let c =
+            Coordinate::from_latlng(12, 35.55280736473704, 50.38718834763207);
+        let (tx, ty) = c.txy();
+        let line = vec![9, 50, 34, 10, 2, 2];
+
+        let mut layer = DynLayer::new("poi");
+        layer.features.push(super::DynFeature {
+            id: Some(1),
+            geometry: DynGeometry::Point(c.clone()),
+            properties: [
+                (String::from("name"), TileValueEnum::from("cafe")),
+                (String::from("rank"), TileValueEnum::from(3u64)),
+            ]
+            .into(),
+        });
+        layer.features.push(super::DynFeature {
+            id: None,
+            geometry: DynGeometry::Other {
+                geom_type: GeomType::LineString,
+                commands: line.clone(),
+            },
+            properties: [(String::from("rank"), TileValueEnum::from(3u64))]
+                .into(),
+        });
+
+        for extent in [4096, 512] {
+            layer.extent = extent;
+            let tile = DynTile { layers: vec![layer.clone()] };
+            let raw = tile.to_tile();
+            assert_eq!(raw.layers[0].keys, ["name", "rank"]);
+            assert_eq!(raw.layers[0].values.len(), 2);
+
+            let pbf = tile.encode();
+            let decoded = DynTile::decode(12, tx, ty, pbf).unwrap();
+            let dl = decoded.layer("poi").unwrap();
+            assert_eq!(dl.extent, extent);
+            assert_eq!(dl.features[0].properties, layer.features[0].properties);
+
+            let DynGeometry::Point(p) = &dl.features[0].geometry else {
+                panic!("not a point")
+            };
+            let max = if extent == 4096 { 5.0 } else { 40.0 };
+            assert!(p.distance_to(&c) < max);
+
+            let DynGeometry::Other { geom_type, commands } =
+                &dl.features[1].geometry
+            else {
+                panic!("not a line")
+            };
+            assert_eq!(*geom_type, GeomType::LineString);
+            assert_eq!(commands, &line);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::{fmt, io};
+
+/// Error of decoding a tile.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// data ends in the middle of a field
+    UnexpectedEof,
+    /// varint longer than ten bytes
+    Varint,
+    /// wire type of a field is not one of 0, 1, 2 or 5
+    WireType(u8),
+    /// string is not valid utf-8
+    Utf8,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::UnexpectedEof => f.write_str("unexpected end of data"),
+            Self::Varint => f.write_str("varint is too long"),
+            Self::WireType(t) => write!(f, "unsupported wire type {t}"),
+            Self::Utf8 => f.write_str("string is not valid utf-8"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
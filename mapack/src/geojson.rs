@@ -0,0 +1,216 @@
+use crate::{Coordinate, Point, Tile, Value};
+use serde_json::{json, Map, Value as Json};
+use std::fmt;
+
+/// Error of reading a GeoJSON feature into a generated layer struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeoJsonError {
+    NotAFeature,
+    /// geometry is not a `Point` with a longitude and a latitude, in
+    /// degrees
+    BadGeometry,
+    /// feature `id` is not an unsigned integer
+    BadId,
+    /// property `key` could not be converted into `expected`
+    BadProperty {
+        key: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAFeature => f.write_str("not a geojson feature"),
+            Self::BadGeometry => f.write_str("geometry is not a valid point"),
+            Self::BadId => f.write_str("id is not an unsigned integer"),
+            Self::BadProperty { key, expected } => {
+                write!(f, "property {key} is not a valid {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeoJsonError {}
+
+/// Types a GeoJSON property can be read into.
+pub trait FromJson: Sized {
+    fn from_json(value: &Json) -> Option<Self>;
+}
+
+impl FromJson for String {
+    fn from_json(value: &Json) -> Option<Self> {
+        value.as_str().map(String::from)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Json) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+macro_rules! from_json_int {
+    ($as:ident, $($ty:ty),*) => {$(
+        impl FromJson for $ty {
+            fn from_json(value: &Json) -> Option<Self> {
+                value.$as()?.try_into().ok()
+            }
+        }
+    )*};
+}
+
+from_json_int!(as_u64, u8, u16, u32, u64);
+from_json_int!(as_i64, i8, i16, i32, i64);
+
+/// tile value form of a json scalar, used for fields without a
+/// [`FromJson`] implementation
+pub fn json_to_value(value: &Json) -> Value {
+    match value {
+        Json::String(v) => Value::from_string(v.clone()),
+        Json::Bool(v) => Value::from_bool(*v),
+        Json::Number(n) => {
+            if let Some(v) = n.as_u64() {
+                Value::from_uint(v)
+            } else if let Some(v) = n.as_i64() {
+                Value::from_int(v)
+            } else {
+                Value { double_value: n.as_f64(), ..Default::default() }
+            }
+        }
+        _ => Value::default(),
+    }
+}
+
+/// Reads the id and the point of a GeoJSON feature, for
+/// `from_geojson_feature` of the generated layer structs.
+pub fn feature_point(
+    zom: u8, feature: &Json,
+) -> Result<(Option<u64>, Coordinate), GeoJsonError> {
+    if feature["type"] != "Feature" {
+        return Err(GeoJsonError::NotAFeature);
+    }
+
+    let geometry = &feature["geometry"];
+    if geometry["type"] != "Point" {
+        return Err(GeoJsonError::BadGeometry);
+    }
+    let coordinates = &geometry["coordinates"];
+    let lng = coordinates[0].as_f64().filter(|v| (-180.0..=180.0).contains(v));
+    let lat = coordinates[1].as_f64().filter(|v| (-90.0..=90.0).contains(v));
+    let (Some(lng), Some(lat)) = (lng, lat) else {
+        return Err(GeoJsonError::BadGeometry);
+    };
+
+    let id = match &feature["id"] {
+        Json::Null => None,
+        id => Some(id.as_u64().ok_or(GeoJsonError::BadId)?),
+    };
+
+    Ok((id, Coordinate::from_latlng(zom, lat, lng)))
+}
+
+/// json form of a tile value, `null` if the value is empty
+pub fn value_to_json(value: &Value) -> Json {
+    if let Some(v) = &value.string_value {
+        json!(v)
+    } else if let Some(v) = value.float_value {
+        json!(v)
+    } else if let Some(v) = value.double_value {
+        json!(v)
+    } else if let Some(v) = value.int_value {
+        json!(v)
+    } else if let Some(v) = value.uint_value {
+        json!(v)
+    } else if let Some(v) = value.sint_value {
+        json!(v)
+    } else if let Some(v) = value.bool_value {
+        json!(v)
+    } else {
+        Json::Null
+    }
+}
+
+fn feature(
+    id: Option<u64>, coordinate: Option<&Coordinate>,
+    properties: Map<String, Json>,
+) -> Json {
+    let geometry = match coordinate {
+        Some(c) => json!({
+            "type": "Point",
+            "coordinates": [c.longitude(), c.latitude()],
+        }),
+        None => Json::Null,
+    };
+
+    let mut feature = json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": properties,
+    });
+    if let Some(id) = id {
+        feature["id"] = json!(id);
+    }
+
+    feature
+}
+
+/// FeatureCollection of decoded points
+pub fn layer_to_geojson<P: Point>(points: &[P]) -> Json {
+    let features = points
+        .iter()
+        .map(|point| {
+            let properties = P::KEYS
+                .iter()
+                .zip(point.values())
+                .map(|(k, v)| (k.to_string(), value_to_json(&v)))
+                .collect();
+            feature(point.id(), Some(point.coordinate()), properties)
+        })
+        .collect::<Vec<_>>();
+
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+impl Tile {
+    /// A FeatureCollection for every layer keyed by the layer name.
+    ///
+    /// features that are not a single point inside of the tile get a `null`
+    /// geometry, see [`Layer::points`](crate::Layer::points). tags out of range of the keys or
+    /// values are skipped.
+    pub fn to_geojson(&self, zom: u8, tx: u32, ty: u32) -> Json {
+        let mut layers = Map::new();
+
+        for layer in self.layers.iter() {
+            let features = layer
+                .features
+                .iter()
+                .map(|f| {
+                    let coordinate = layer
+                        .points(zom, tx, ty, f)
+                        .filter(|p| p.len() == 1)
+                        .map(|mut p| p.remove(0));
+
+                    let properties = f
+                        .tags
+                        .chunks_exact(2)
+                        .filter_map(|kv| {
+                            let key = layer.keys.get(kv[0] as usize)?;
+                            let value = layer.values.get(kv[1] as usize)?;
+                            Some((key.clone(), value_to_json(value)))
+                        })
+                        .collect();
+
+                    feature(f.id, coordinate.as_ref(), properties)
+                })
+                .collect::<Vec<_>>();
+
+            layers.insert(
+                layer.name().to_string(),
+                json!({ "type": "FeatureCollection", "features": features }),
+            );
+        }
+
+        Json::Object(layers)
+    }
+}
@@ -0,0 +1,116 @@
+//! helpers for the geometry command integers of MVT 2.1
+
+pub(crate) const MOVE_TO: u32 = 1;
+pub(crate) const LINE_TO: u32 = 2;
+pub(crate) const CLOSE_PATH: u32 = 7;
+
+pub(crate) fn zigzag(v: i64) -> u32 {
+    ((v << 1) ^ (v >> 63)) as u32
+}
+
+pub(crate) fn unzigzag(v: u32) -> i64 {
+    ((v >> 1) as i64) ^ -((v & 1) as i64)
+}
+
+pub(crate) fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+/// id and count of every command, checking that each one has the right
+/// number of parameters
+pub(crate) fn commands(
+    geometry: &[u32],
+) -> Result<Vec<(u32, u32)>, &'static str> {
+    let mut commands = Vec::new();
+    let mut idx = 0;
+
+    while idx < geometry.len() {
+        let (id, count) = (geometry[idx] & 0x7, geometry[idx] >> 3);
+        idx += 1;
+
+        let params = match id {
+            MOVE_TO | LINE_TO if count == 0 => {
+                return Err("command with a count of zero")
+            }
+            MOVE_TO | LINE_TO => count as usize * 2,
+            CLOSE_PATH if count != 1 => {
+                return Err("ClosePath with a count other than one")
+            }
+            CLOSE_PATH => 0,
+            _ => return Err("unknown command id"),
+        };
+
+        if geometry.len() - idx < params {
+            return Err("missing command parameters");
+        }
+        idx += params;
+        commands.push((id, count));
+    }
+
+    Ok(commands)
+}
+
+/// positions of a geometry made of a single `MoveTo` command
+pub(crate) fn decode_points(geometry: &[u32]) -> Option<Vec<(i64, i64)>> {
+    let (&cmd, params) = geometry.split_first()?;
+    let count = (cmd >> 3) as usize;
+    if cmd & 0x7 != MOVE_TO || count == 0 || params.len() != count * 2 {
+        return None;
+    }
+
+    let (mut x, mut y) = (0i64, 0i64);
+    let points = params
+        .chunks_exact(2)
+        .map(|p| {
+            x += unzigzag(p[0]);
+            y += unzigzag(p[1]);
+            (x, y)
+        })
+        .collect();
+
+    Some(points)
+}
+
+pub(crate) fn encode_points(points: &[(i64, i64)]) -> Vec<u32> {
+    let mut geometry = Vec::with_capacity(1 + points.len() * 2);
+    geometry.push(command(MOVE_TO, points.len() as u32));
+
+    let (mut px, mut py) = (0i64, 0i64);
+    for &(x, y) in points {
+        geometry.push(zigzag(x - px));
+        geometry.push(zigzag(y - py));
+        (px, py) = (x, y);
+    }
+
+    geometry
+}
+
+/// Positions of every path of a geometry that passed [`commands`], a path
+/// starts at each `MoveTo` point and goes on with the `LineTo` points after
+/// it. `ClosePath` does not repeat the first point.
+pub(crate) fn decode_paths(geometry: &[u32]) -> Vec<Vec<(i64, i64)>> {
+    let mut paths = Vec::<Vec<(i64, i64)>>::new();
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut idx = 0;
+
+    while idx < geometry.len() {
+        let (id, count) = (geometry[idx] & 0x7, geometry[idx] >> 3);
+        idx += 1;
+        if id == CLOSE_PATH {
+            continue;
+        }
+
+        for _ in 0..count {
+            let Some(p) = geometry.get(idx..idx + 2) else { return paths };
+            idx += 2;
+            x = x.wrapping_add(unzigzag(p[0]));
+            y = y.wrapping_add(unzigzag(p[1]));
+            match (id, paths.last_mut()) {
+                (LINE_TO, Some(path)) => path.push((x, y)),
+                _ => paths.push(vec![(x, y)]),
+            }
+        }
+    }
+
+    paths
+}
@@ -0,0 +1,330 @@
+use crate::coordinate::world;
+use crate::query::BBox;
+use crate::{Coordinate, Point};
+
+/// children of a node of the tree
+const NODE_SIZE: usize = 16;
+
+/// A packed Hilbert R-tree over the positions of many points.
+///
+/// Points are sorted along a Hilbert curve, which keeps the points of a
+/// tile next to each other on every zoom level. the tree on top of them
+/// answers bounding box and buffered tile queries.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    /// latitude and longitude, by input index
+    latlng: Vec<(f64, f64)>,
+    /// position on the map at zoom 0, by input index
+    world: Vec<(f64, f64)>,
+    /// input indices in hilbert order
+    order: Vec<usize>,
+    /// `world` scaled to 32 bit cells, in hilbert order
+    cells: Vec<(u32, u32)>,
+    /// boxes of the tree, `[x0, y0, x1, y1]`. the first level holds the
+    /// boxes of every `NODE_SIZE` points, each next level the boxes of
+    /// `NODE_SIZE` nodes of the level before it
+    levels: Vec<Vec<[f64; 4]>>,
+}
+
+impl SpatialIndex {
+    pub fn new<'a>(
+        coordinates: impl IntoIterator<Item = &'a Coordinate>,
+    ) -> Self {
+        let latlng =
+            coordinates.into_iter().map(Coordinate::latlng).collect::<Vec<_>>();
+        let world = latlng
+            .iter()
+            .map(|&(lat, lng)| world(lat, lng))
+            .collect::<Vec<_>>();
+
+        let cell = |v: f64| (v * 4294967296.0) as u32;
+        let mut order = (0..latlng.len())
+            .map(|i| {
+                let (x, y) = (cell(world[i].0), cell(world[i].1));
+                (hilbert(x, y), i, (x, y))
+            })
+            .collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&(h, i, _)| (h, i));
+
+        let cells = order.iter().map(|o| o.2).collect();
+        let order = order.into_iter().map(|o| o.1).collect::<Vec<_>>();
+
+        let leaves = order
+            .chunks(NODE_SIZE)
+            .map(|chunk| {
+                chunk.iter().fold(EMPTY, |b, &i| {
+                    let (x, y) = world[i];
+                    [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)]
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut levels = vec![leaves];
+        while let Some(last) = levels.last().filter(|l| l.len() > 1) {
+            let next = last
+                .chunks(NODE_SIZE)
+                .map(|chunk| {
+                    chunk.iter().fold(EMPTY, |b, c| {
+                        [
+                            b[0].min(c[0]),
+                            b[1].min(c[1]),
+                            b[2].max(c[2]),
+                            b[3].max(c[3]),
+                        ]
+                    })
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Self { latlng, world, order, cells, levels }
+    }
+
+    pub fn from_points<P: Point>(points: &[P]) -> Self {
+        Self::new(points.iter().map(Point::coordinate))
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// the point at `idx` on zoom level `zom`
+    pub fn coordinate(&self, idx: usize, zom: u8) -> Coordinate {
+        Coordinate::from_world(zom, self.latlng[idx], self.world[idx])
+    }
+
+    /// indices of the points inside of `bbox`, in input order
+    pub fn within_bbox(&self, bbox: &BBox) -> Vec<usize> {
+        let (_, y0) = world(bbox.north, 0.0);
+        let (_, y1) = world(bbox.south, 0.0);
+
+        let mut out = Vec::new();
+        for (west, east) in bbox.lng_ranges() {
+            let (x0, _) = world(0.0, west);
+            let (x1, _) = world(0.0, east);
+            self.search([x0, y0, x1, y1], &mut out, |x, y| {
+                (x0..=x1).contains(&x) && (y0..=y1).contains(&y)
+            });
+        }
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Indices of the points in tile `zom`/`tx`/`ty` and within `buffer`
+    /// extent units around it, in input order. a tile is 4096 units wide,
+    /// zoom levels past 32 have no points.
+    pub fn tile(&self, zom: u8, tx: u32, ty: u32, buffer: u32) -> Vec<usize> {
+        if zom > 32 {
+            return Vec::new();
+        }
+
+        let n = (1u64 << zom) as f64;
+        let b = buffer as f64 / 4096.0;
+        let x0 = (tx as f64 - b) / n;
+        let x1 = (tx as f64 + 1.0 + b) / n;
+        let y0 = (ty as f64 - b) / n;
+        let y1 = (ty as f64 + 1.0 + b) / n;
+
+        // buffers wrap around the antimeridian
+        let mut ranges = vec![(x0.max(0.0), x1.min(1.0))];
+        if x0 < 0.0 {
+            ranges.push((x0 + 1.0, 1.0));
+        }
+        if x1 > 1.0 {
+            ranges.push((0.0, x1 - 1.0));
+        }
+
+        let mut out = Vec::new();
+        for (x0, x1) in ranges {
+            self.search([x0, y0, x1, y1], &mut out, |x, y| {
+                x0 <= x && x < x1 && y0 <= y && y < y1
+            });
+        }
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Every non-empty tile of zoom level `zom` with the indices of its
+    /// points, in hilbert order. the points of each tile are a slice of
+    /// the index, no point is looked up on the way.
+    pub fn tiles(
+        &self, zom: u8,
+    ) -> impl Iterator<Item = ((u32, u32), &[usize])> + '_ {
+        let shift = 32 - zom.min(32) as u32;
+        let tile = move |&(x, y): &(u32, u32)| {
+            (
+                x.checked_shr(shift).unwrap_or(0),
+                y.checked_shr(shift).unwrap_or(0),
+            )
+        };
+
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            let first = tile(self.cells.get(start)?);
+            let len = self.cells[start..]
+                .iter()
+                .position(|c| tile(c) != first)
+                .unwrap_or(self.cells.len() - start);
+            let points = &self.order[start..start + len];
+            start += len;
+            Some((first, points))
+        })
+    }
+
+    fn search(
+        &self, rect: [f64; 4], out: &mut Vec<usize>,
+        f: impl Fn(f64, f64) -> bool,
+    ) {
+        let intersects = |b: &[f64; 4]| {
+            b[0] <= rect[2]
+                && rect[0] <= b[2]
+                && b[1] <= rect[3]
+                && rect[1] <= b[3]
+        };
+
+        let top = self.levels.len() - 1;
+        let mut stack =
+            (0..self.levels[top].len()).map(|i| (top, i)).collect::<Vec<_>>();
+        while let Some((level, i)) = stack.pop() {
+            if !intersects(&self.levels[level][i]) {
+                continue;
+            }
+
+            let children = i * NODE_SIZE..(i + 1) * NODE_SIZE;
+            if level > 0 {
+                let len = self.levels[level - 1].len();
+                stack.extend(
+                    (children.start..children.end.min(len))
+                        .map(|c| (level - 1, c)),
+                );
+                continue;
+            }
+
+            let children = children.start..children.end.min(self.order.len());
+            for &idx in &self.order[children] {
+                let (x, y) = self.world[idx];
+                if f(x, y) {
+                    out.push(idx);
+                }
+            }
+        }
+    }
+}
+
+const EMPTY: [f64; 4] =
+    [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+
+/// Distance of the cell `x`, `y` along a Hilbert curve over the 2^32 by
+/// 2^32 cells. every aligned square of cells is a single run of distances.
+fn hilbert(x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (x as u64, y as u64);
+    let mut d = 0u64;
+    let mut s = 1u64 << 31;
+    while s > 0 {
+        let rx = (x & s > 0) as u64;
+        let ry = (y & s > 0) as u64;
+        d += s * s * ((3 * rx) ^ ry);
+
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x &= s - 1;
+        y &= s - 1;
+        s >>= 1;
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hilbert, SpatialIndex};
+    use crate::query::BBox;
+    use crate::Coordinate;
+    use mapack_fixtures::Rng;
+
+    #[test]
+    fn hilbert_runs() {
+        let mut cells = (0..16u32)
+            .flat_map(|x| (0..16u32).map(move |y| (x << 28, y << 28)))
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|&(x, y)| hilbert(x, y));
+
+        let mut seen = std::collections::HashSet::new();
+        let mut last = None;
+        for (x, y) in cells {
+            let quad = (x >> 30, y >> 30);
+            if last != Some(quad) {
+                assert!(seen.insert(quad), "quadrant {quad:?} is split");
+                last = Some(quad);
+            }
+        }
+    }
+
+    #[test]
+    fn index() {
+        let mut rng = Rng(11);
+        let coordinates = (0..2000)
+            .map(|_| {
+                let lat = (rng.next_u32() % 170_000) as f64 / 1000.0 - 85.0;
+                let lng = (rng.next_u32() % 360_000) as f64 / 1000.0 - 180.0;
+                Coordinate::from_latlng(8, lat, lng)
+            })
+            .collect::<Vec<_>>();
+        let index = SpatialIndex::new(&coordinates);
+        assert_eq!(index.len(), 2000);
+
+        for zom in [0, 3, 8] {
+            let mut total = 0;
+            for ((tx, ty), points) in index.tiles(zom) {
+                let mut points = points.to_vec();
+                points.sort_unstable();
+                for &i in &points {
+                    assert_eq!(coordinates[i].with_zoom(zom).txy(), (tx, ty));
+                    let c = index.coordinate(i, zom);
+                    assert_eq!(c.sxy(), coordinates[i].with_zoom(zom).sxy());
+                }
+                assert_eq!(index.tile(zom, tx, ty, 0), points);
+                total += points.len();
+            }
+            assert_eq!(total, 2000);
+        }
+
+        assert!(index.tile(33, 0, 0, 0).is_empty());
+        assert!(index.tile(64, 0, 0, 4096).is_empty());
+        assert!(index.tile(255, u32::MAX, u32::MAX, 0).is_empty());
+
+        let (tx, ty) = coordinates[0].with_zoom(6).txy();
+        let buffered = index.tile(6, tx, ty, 2048);
+        let expected = (0..2000)
+            .filter(|&i| {
+                let c = coordinates[i].with_zoom(7);
+                let (dx, dy) = (
+                    c.tx() as i64 - tx as i64 * 2,
+                    c.ty() as i64 - ty as i64 * 2,
+                );
+                (-1..=2).contains(&dx) && (-1..=2).contains(&dy)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(buffered, expected);
+
+        for bbox in [
+            BBox::new(-20.0, -10.0, 40.0, 30.0),
+            BBox::new(170.0, -60.0, -170.0, 60.0),
+        ] {
+            let expected = (0..2000)
+                .filter(|&i| bbox.contains(&coordinates[i]))
+                .collect::<Vec<_>>();
+            assert!(!expected.is_empty());
+            assert_eq!(index.within_bbox(&bbox), expected);
+        }
+    }
+}
@@ -1,11 +1,53 @@
 pub use mapack_macros::mapack;
 
+mod builder;
+pub use builder::LayerBuilder;
+pub mod cluster;
+mod compression;
+pub use compression::{
+    compress, decompress, decompress_with_limit, Compression, MAX_DECOMPRESSED,
+};
 mod coordinate;
 pub use coordinate::Coordinate;
-pub use protobuf;
+#[cfg(feature = "csv")]
+pub mod csv;
+mod diff;
+pub use diff::{diff, Change};
+mod dynamic;
+pub use dynamic::{DynFeature, DynGeometry, DynLayer, DynTile, TileValueEnum};
+mod error;
+pub use error::Error;
+pub mod geojson;
+mod geometry;
+mod index;
+pub use index::SpatialIndex;
+#[cfg(feature = "mbtiles")]
+mod mbtiles;
+#[cfg(feature = "mbtiles")]
+pub use mbtiles::MbTiles;
+mod limit;
+pub use limit::{DropMode, Limit};
+mod point;
+pub use point::{Point, PointTile};
+pub use serde_json;
+mod pyramid;
+pub use pyramid::{Pyramid, TileSink};
+pub mod query;
+#[cfg(feature = "server")]
+pub mod server;
+mod store;
+pub use store::{DirStore, MemStore, TileLocks, TileStore};
+mod tile_id;
+pub use tile_id::TileId;
+pub mod tilejson;
+mod validate;
+pub use validate::{validate, Rule, Violation};
 mod vector_tile;
-pub use vector_tile::tile::{Feature, GeomType, Layer, Value};
-pub use vector_tile::Tile;
+pub use vector_tile::{
+    write_layer, Feature, GeomType, Layer, Tile, Value, ValueRef,
+};
+mod zoom;
+pub use zoom::{merge_children, overzoom};
 
 impl Value {
     pub fn from_string(value: String) -> Self {
@@ -0,0 +1,130 @@
+use crate::{Coordinate, Point};
+use std::collections::HashMap;
+
+/// How points are dropped from a tile that is over its [`Limit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DropMode {
+    /// drop the lowest priority points first
+    #[default]
+    LowestPriority,
+    /// raise the minimum spacing between points until the tile fits,
+    /// similar to tippecanoe's `--drop-densest-as-needed`.
+    /// priority decides which of two close points is kept
+    Densest,
+}
+
+/// Caps the number of features or bytes of a single tile.
+#[derive(Debug, Clone, Default)]
+pub struct Limit {
+    max_features: Option<usize>,
+    max_bytes: Option<usize>,
+    mode: DropMode,
+}
+
+impl Limit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_features(mut self, max: usize) -> Self {
+        self.max_features = Some(max);
+        self
+    }
+
+    /// size limit of the encoded tile
+    pub fn with_max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = Some(max);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: DropMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn max_features(&self) -> Option<usize> {
+        self.max_features
+    }
+
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    pub fn mode(&self) -> DropMode {
+        self.mode
+    }
+
+    /// Drops points from a decoded layer that is over `max_features`.
+    /// points earlier in the layer have a higher priority, `max_bytes`
+    /// needs an encoded tile and is not used here.
+    pub fn apply<P: Point>(&self, points: &mut Vec<P>) {
+        if let Some(max) = self.max_features {
+            let layer = std::mem::take(points);
+            *points = self.select(layer, max, |p| p.coordinate());
+        }
+    }
+
+    /// Keeps at most `n` of `items`, which must be sorted from the
+    /// highest to the lowest priority.
+    pub(crate) fn select<T>(
+        &self, mut items: Vec<T>, n: usize,
+        coordinate: impl Fn(&T) -> &Coordinate,
+    ) -> Vec<T> {
+        if items.len() <= n {
+            return items;
+        }
+
+        if self.mode == DropMode::LowestPriority {
+            items.truncate(n);
+            return items;
+        }
+
+        let mut gap = 1;
+        loop {
+            let mut kept = spaced(&items, gap, &coordinate);
+            if kept.len() <= n || gap >= 4096 {
+                kept.truncate(n);
+                let mut kept = kept.into_iter().peekable();
+                let mut idx = 0;
+                items.retain(|_| {
+                    let keep = kept.next_if_eq(&idx).is_some();
+                    idx += 1;
+                    keep
+                });
+                return items;
+            }
+            gap *= 2;
+        }
+    }
+}
+
+/// indices of the items that are at least `gap` apart, preferring the
+/// earlier ones
+fn spaced<T>(
+    items: &[T], gap: i64, coordinate: impl Fn(&T) -> &Coordinate,
+) -> Vec<usize> {
+    let mut grid = HashMap::<(i64, i64), Vec<(i64, i64)>>::new();
+    let mut kept = Vec::<usize>::new();
+
+    'items: for (idx, item) in items.iter().enumerate() {
+        let (x, y) = coordinate(item).sxy();
+        let (x, y) = (x as i64, y as i64);
+
+        for gx in x / gap - 1..=x / gap + 1 {
+            for gy in y / gap - 1..=y / gap + 1 {
+                let Some(cell) = grid.get(&(gx, gy)) else { continue };
+                for (px, py) in cell {
+                    let (dx, dy) = (px - x, py - y);
+                    if dx * dx + dy * dy < gap * gap {
+                        continue 'items;
+                    }
+                }
+            }
+        }
+
+        grid.entry((x / gap, y / gap)).or_default().push((x, y));
+        kept.push(idx);
+    }
+
+    kept
+}
@@ -0,0 +1,193 @@
+use crate::{TileId, TileStore};
+use rusqlite::{Connection, OptionalExtension};
+use std::io;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS metadata (name TEXT PRIMARY KEY, value TEXT);
+    CREATE TABLE IF NOT EXISTS tiles (
+        zoom_level INTEGER,
+        tile_column INTEGER,
+        tile_row INTEGER,
+        tile_data BLOB
+    );
+    CREATE UNIQUE INDEX IF NOT EXISTS tile_index
+        ON tiles (zoom_level, tile_column, tile_row);
+";
+
+fn sql_err(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Tiles stored in an [MBTiles](https://github.com/mapbox/mbtiles-spec)
+/// sqlite database. rows are flipped to TMS on the way in and out, tiles
+/// past the edge of the map are an [`io::ErrorKind::InvalidInput`] error.
+#[derive(Debug)]
+pub struct MbTiles {
+    conn: Mutex<Connection>,
+}
+
+impl MbTiles {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let conn = Connection::open(path).map_err(sql_err)?;
+        conn.execute_batch(SCHEMA).map_err(sql_err)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    pub fn open_in_memory() -> io::Result<Self> {
+        let conn = Connection::open_in_memory().map_err(sql_err)?;
+        conn.execute_batch(SCHEMA).map_err(sql_err)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_metadata(&self, name: &str, value: &str) -> io::Result<()> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO metadata (name, value) VALUES (?1, ?2)",
+                (name, value),
+            )
+            .map_err(sql_err)?;
+        Ok(())
+    }
+
+    pub fn metadata(&self, name: &str) -> io::Result<Option<String>> {
+        self.conn()
+            .query_row(
+                "SELECT value FROM metadata WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_err)
+    }
+}
+
+/// TMS row of the tile, `InvalidInput` for tiles past the edge of the map
+/// instead of a row of another tile
+fn tms_row(zom: u8, tx: u32, ty: u32) -> io::Result<u32> {
+    TileId::new(zom, tx, ty).tms_y().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("tile {zom}/{tx}/{ty} is out of range"),
+        )
+    })
+}
+
+fn get(
+    conn: &Connection, zom: u8, tx: u32, row: u32,
+) -> rusqlite::Result<Option<Vec<u8>>> {
+    conn.query_row(
+        "SELECT tile_data FROM tiles
+        WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+        (zom, tx, row),
+        |row| row.get::<_, Vec<u8>>(0),
+    )
+    .optional()
+    .map(|data| data.filter(|d| !d.is_empty()))
+}
+
+fn put(
+    conn: &Connection, zom: u8, tx: u32, row: u32, data: &[u8],
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO tiles
+        (zoom_level, tile_column, tile_row, tile_data) VALUES (?1, ?2, ?3, ?4)",
+        (zom, tx, row, data),
+    )?;
+    Ok(())
+}
+
+fn remove(
+    conn: &Connection, zom: u8, tx: u32, row: u32,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM tiles
+        WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+        (zom, tx, row),
+    )?;
+    Ok(())
+}
+
+impl TileStore for MbTiles {
+    fn get(&self, zom: u8, tx: u32, ty: u32) -> io::Result<Option<Vec<u8>>> {
+        let row = tms_row(zom, tx, ty)?;
+        get(&self.conn(), zom, tx, row).map_err(sql_err)
+    }
+
+    fn put(&self, zom: u8, tx: u32, ty: u32, data: &[u8]) -> io::Result<()> {
+        let row = tms_row(zom, tx, ty)?;
+        put(&self.conn(), zom, tx, row, data).map_err(sql_err)
+    }
+
+    fn remove(&self, zom: u8, tx: u32, ty: u32) -> io::Result<()> {
+        let row = tms_row(zom, tx, ty)?;
+        remove(&self.conn(), zom, tx, row).map_err(sql_err)
+    }
+
+    fn update<F, E>(&self, zom: u8, tx: u32, ty: u32, f: F) -> Result<(), E>
+    where
+        F: FnOnce(Option<Vec<u8>>) -> Result<Option<Vec<u8>>, E>,
+        E: From<io::Error>,
+    {
+        let row = tms_row(zom, tx, ty)?;
+        let mut conn = self.conn();
+        let trx = conn.transaction().map_err(sql_err)?;
+
+        let data = get(&trx, zom, tx, row).map_err(sql_err)?;
+        match f(data)? {
+            Some(data) if !data.is_empty() => {
+                put(&trx, zom, tx, row, &data).map_err(sql_err)?
+            }
+            _ => remove(&trx, zom, tx, row).map_err(sql_err)?,
+        }
+
+        trx.commit().map_err(sql_err)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MbTiles;
+    use crate::TileStore;
+
+    #[test]
+    fn tms_rows() {
+        let db = MbTiles::open_in_memory().unwrap();
+        db.put(2, 1, 0, b"tile").unwrap();
+
+        let row: u32 = db
+            .conn()
+            .query_row("SELECT tile_row FROM tiles", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(row, 3);
+        assert_eq!(db.get(2, 1, 0).unwrap().as_deref(), Some(&b"tile"[..]));
+
+        db.update(2, 1, 0, |_| std::io::Result::Ok(None)).unwrap();
+        assert!(db.get(2, 1, 0).unwrap().is_none());
+
+        // rows past the edge are not flipped onto another tile
+        for (zom, tx, ty) in [(2, 1, 4), (2, 4, 1), (255, 0, 0)] {
+            let kind = |e: std::io::Error| e.kind();
+            let invalid = Err(std::io::ErrorKind::InvalidInput);
+            assert_eq!(db.put(zom, tx, ty, b"tile").map_err(kind), invalid);
+            assert_eq!(db.get(zom, tx, ty).map(|_| ()).map_err(kind), invalid);
+            assert_eq!(db.remove(zom, tx, ty).map_err(kind), invalid);
+            let update = db.update(zom, tx, ty, |_| Ok(Some(b"tile".to_vec())));
+            assert_eq!(update.map_err(kind), invalid);
+        }
+        let count: u32 = db
+            .conn()
+            .query_row("SELECT count(*) FROM tiles", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+
+        db.set_metadata("format", "pbf").unwrap();
+        assert_eq!(db.metadata("format").unwrap().as_deref(), Some("pbf"));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{
+    compress, Compression, Coordinate, Error, Feature, Limit, Value, ValueRef,
+};
+use std::io::{self, Write};
+
+/// Implemented by every layer struct generated by [`mapack!`](crate::mapack).
+pub trait Point: Sized {
+    /// name of the layer
+    const NAME: &'static str;
+    /// keys of the layer, in the order of [`Point::values`]
+    const KEYS: &'static [&'static str];
+
+    /// a point with default values
+    fn new(coordinate: Coordinate) -> Self;
+
+    fn coordinate(&self) -> &Coordinate;
+    fn coordinate_mut(&mut self) -> &mut Coordinate;
+    fn id(&self) -> Option<u64>;
+    fn set_id(&mut self, id: Option<u64>);
+
+    /// encoded value of every key in [`Point::KEYS`]
+    fn values(&self) -> Vec<Value>;
+
+    /// calls `f` with the value of every key in [`Point::KEYS`], borrowing
+    /// strings instead of cloning them
+    fn for_each_value(&self, mut f: impl FnMut(ValueRef<'_>)) {
+        for value in self.values().iter() {
+            f(value.into());
+        }
+    }
+
+    /// Sets the field of `key` from its text form. returns `Ok(false)`
+    /// for unknown keys and the type name of the field if `value` could
+    /// not be parsed.
+    fn set_from_str(
+        &mut self, key: &str, value: &str,
+    ) -> Result<bool, &'static str>;
+
+    fn decode_point(
+        zom: u8, tx: u32, ty: u32, feature: &Feature, values: &[Value],
+    ) -> Result<Self, &'static str>;
+}
+
+/// Implemented by the `Tile` struct generated by [`mapack!`](crate::mapack).
+pub trait PointTile: Sized {
+    fn new() -> Self;
+    /// Gzip and zstd compressed tiles are decompressed first, up to
+    /// [`MAX_DECOMPRESSED`](crate::MAX_DECOMPRESSED) bytes.
+    ///
+    /// Features that are not a single point inside of the tile are skipped.
+    /// that includes buffer points outside of `0..=4096` written by other
+    /// producers, see [`Coordinate::try_from_geometry`].
+    fn decode(zom: u8, tx: u32, ty: u32, pbf: Vec<u8>) -> Result<Self, Error>;
+    fn encode(&self) -> Vec<u8>;
+
+    /// streams the encoded tile into `w`, see [`write_layer`](crate::write_layer)
+    fn encode_to<W: Write>(&self, w: W) -> io::Result<()>;
+
+    fn encode_compressed(
+        &self, compression: Compression,
+    ) -> io::Result<Vec<u8>> {
+        compress(self.encode(), compression)
+    }
+
+    /// calls `f` with the coordinate of every point in every layer and
+    /// removes the points it returns `false` for
+    fn retain_mut(&mut self, f: impl FnMut(&mut Coordinate) -> bool);
+
+    /// moves every point of `other` into `self`
+    fn append(&mut self, other: &mut Self);
+
+    /// Moves every point of `others` into `self`, taking one point of each
+    /// tile in turn. points keep their order within a tile and no tile
+    /// comes first for the whole layer, see [`Limit::apply`].
+    fn interleave(&mut self, others: &mut [Self]);
+
+    /// applies `limit` to every layer, see [`Limit::apply`]
+    fn apply_limit(&mut self, limit: &Limit);
+}
@@ -0,0 +1,160 @@
+use crate::coordinate::MAX_ZOOM;
+use crate::{write_layer, Coordinate, Limit, Point, SpatialIndex, TileStore};
+use std::cmp::Reverse;
+use std::io;
+use std::sync::mpsc::{Sender, SyncSender};
+
+/// Receives the tiles generated by a [`Pyramid`].
+pub trait TileSink {
+    fn put(
+        &mut self, zom: u8, tx: u32, ty: u32, data: Vec<u8>,
+    ) -> io::Result<()>;
+}
+
+impl<S: TileStore> TileSink for S {
+    fn put(
+        &mut self, zom: u8, tx: u32, ty: u32, data: Vec<u8>,
+    ) -> io::Result<()> {
+        TileStore::put(self, zom, tx, ty, &data)
+    }
+}
+
+impl TileSink for Sender<(u8, u32, u32, Vec<u8>)> {
+    fn put(
+        &mut self, zom: u8, tx: u32, ty: u32, data: Vec<u8>,
+    ) -> io::Result<()> {
+        self.send((zom, tx, ty, data))
+            .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
+    }
+}
+
+impl TileSink for SyncSender<(u8, u32, u32, Vec<u8>)> {
+    fn put(
+        &mut self, zom: u8, tx: u32, ty: u32, data: Vec<u8>,
+    ) -> io::Result<()> {
+        self.send((zom, tx, ty, data))
+            .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
+    }
+}
+
+/// Generates every non-empty tile of a layer between two zoom levels.
+#[derive(Debug, Clone)]
+pub struct Pyramid {
+    min_zoom: u8,
+    max_zoom: u8,
+    limit: Option<Limit>,
+}
+
+impl Pyramid {
+    /// zoom levels are clamped to 22, the maximum zoom of [`Coordinate`]
+    pub fn new(min_zoom: u8, max_zoom: u8) -> Self {
+        let (min_zoom, max_zoom) =
+            (min_zoom.min(MAX_ZOOM), max_zoom.min(MAX_ZOOM));
+        Self { min_zoom, max_zoom, limit: None }
+    }
+
+    /// Drop points from tiles that are over `limit`. a tile that does not
+    /// fit `max_bytes` with a single point is skipped.
+    pub fn with_limit(mut self, limit: Limit) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Buckets the points by their tile on every zoom level, encodes each
+    /// bucket as a tile with a single `P::NAME` layer and hands it to
+    /// `sink`. returns the number of tiles written.
+    ///
+    /// Points are bucketed through a [`SpatialIndex`], which sorts them
+    /// once instead of projecting every point on every zoom level.
+    pub fn build<P, I, S>(&self, points: I, sink: &mut S) -> io::Result<usize>
+    where
+        P: Point,
+        I: IntoIterator<Item = P>,
+        S: TileSink,
+    {
+        self.build_by_key(points, sink, |_| 0)
+    }
+
+    /// Same as [`Pyramid::build`], when a tile is over the limit points
+    /// with a higher `key` are kept over the ones with a lower `key`.
+    pub fn build_by_key<P, I, S, K, F>(
+        &self, points: I, sink: &mut S, key: F,
+    ) -> io::Result<usize>
+    where
+        P: Point,
+        I: IntoIterator<Item = P>,
+        S: TileSink,
+        K: Ord,
+        F: Fn(&P) -> K,
+    {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let index = SpatialIndex::from_points(&points);
+
+        let mut count = 0;
+        for zom in self.min_zoom..=self.max_zoom {
+            let mut tiles = index.tiles(zom).collect::<Vec<_>>();
+            tiles.sort_unstable_by_key(|(txy, _)| *txy);
+
+            for ((tx, ty), idxs) in tiles {
+                let mut idxs = idxs.to_vec();
+                idxs.sort_unstable();
+                let bucket = idxs
+                    .into_iter()
+                    .map(|idx| (idx, index.coordinate(idx, zom)))
+                    .collect();
+                if self.put(&points, bucket, (zom, tx, ty), sink, &key)? {
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// `false` if every point of the tile is dropped and nothing is written
+    fn put<P, S, K, F>(
+        &self, points: &[P], mut bucket: Vec<(usize, Coordinate)>,
+        (zom, tx, ty): (u8, u32, u32), sink: &mut S, key: F,
+    ) -> io::Result<bool>
+    where
+        P: Point,
+        S: TileSink,
+        K: Ord,
+        F: Fn(&P) -> K,
+    {
+        let Some(limit) = &self.limit else {
+            sink.put(zom, tx, ty, encode_tile(points, &bucket))?;
+            return Ok(true);
+        };
+
+        bucket.sort_by_key(|(idx, _)| Reverse(key(&points[*idx])));
+        if let Some(max) = limit.max_features() {
+            bucket = limit.select(bucket, max, |(_, c)| c);
+        }
+
+        let mut data = encode_tile(points, &bucket);
+        if let Some(max) = limit.max_bytes() {
+            while data.len() > max && !bucket.is_empty() {
+                let n = bucket.len() * max / data.len();
+                let n = n.min(bucket.len() - 1);
+                bucket = limit.select(bucket, n, |(_, c)| c);
+                data = encode_tile(points, &bucket);
+            }
+        }
+
+        if bucket.is_empty() {
+            return Ok(false);
+        }
+        sink.put(zom, tx, ty, data)?;
+        Ok(true)
+    }
+}
+
+fn encode_tile<P: Point>(
+    points: &[P], bucket: &[(usize, Coordinate)],
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    let points = bucket.iter().map(|(idx, c)| (&points[*idx], c));
+    write_layer(&mut data, points).expect("writing to a Vec does not fail");
+    data
+}
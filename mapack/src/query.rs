@@ -0,0 +1,254 @@
+//! Spatial queries over the points of a layer.
+
+use crate::coordinate::EARTH_RADIUS;
+use crate::{Coordinate, Point};
+use std::collections::HashMap;
+
+/// A latitude and longitude box, `west > east` crosses the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl BBox {
+    pub fn new(west: f64, south: f64, east: f64, north: f64) -> Self {
+        Self { west, south, east, north }
+    }
+
+    /// smallest box holding every point within `meters` of `center`
+    pub fn around(center: &Coordinate, meters: f64) -> Self {
+        let (lat, lng) = center.latlng();
+        // a millimeter of slack for the rounding of `distance_to`
+        let angle = ((meters + 0.001) / EARTH_RADIUS).max(0.0);
+        let south = lat - angle.to_degrees();
+        let north = lat + angle.to_degrees();
+        if south <= -90.0 || north >= 90.0 || angle >= std::f64::consts::PI {
+            return Self::new(-180.0, south.max(-90.0), 180.0, north.min(90.0));
+        }
+
+        let dlng = (angle.sin() / lat.to_radians().cos()).min(1.0).asin();
+        let dlng = dlng.to_degrees();
+        if dlng >= 180.0 {
+            return Self::new(-180.0, south, 180.0, north);
+        }
+        let wrap = |v: f64| {
+            if v < -180.0 {
+                v + 360.0
+            } else if v > 180.0 {
+                v - 360.0
+            } else {
+                v
+            }
+        };
+        Self::new(wrap(lng - dlng), south, wrap(lng + dlng), north)
+    }
+
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        let (lat, lng) = coordinate.latlng();
+        let lng_inside = if self.west <= self.east {
+            (self.west..=self.east).contains(&lng)
+        } else {
+            lng >= self.west || lng <= self.east
+        };
+        lng_inside && (self.south..=self.north).contains(&lat)
+    }
+
+    /// the box split at the antimeridian, as `(west, east)` ranges
+    pub(crate) fn lng_ranges(&self) -> impl Iterator<Item = (f64, f64)> {
+        let (first, second) = if self.west <= self.east {
+            ((self.west, self.east), None)
+        } else {
+            ((self.west, 180.0), Some((-180.0, self.east)))
+        };
+        std::iter::once(first).chain(second)
+    }
+}
+
+/// Queries on a slice of points by scanning all of them, see
+/// [`PointIndex`] for repeated queries on the same points.
+pub trait PointQuery<P: Point> {
+    /// points within `meters` of `center`, in input order
+    fn within_radius(&self, center: &Coordinate, meters: f64) -> Vec<&P>;
+
+    /// points inside of `bbox`, in input order
+    fn within_bbox(&self, bbox: &BBox) -> Vec<&P>;
+
+    /// the `k` points nearest to `center` with their distance in meters,
+    /// nearest first
+    fn nearest(&self, center: &Coordinate, k: usize) -> Vec<(&P, f64)>;
+
+    fn index(&self) -> PointIndex<'_, P>;
+}
+
+impl<P: Point> PointQuery<P> for [P] {
+    fn within_radius(&self, center: &Coordinate, meters: f64) -> Vec<&P> {
+        self.iter()
+            .filter(|p| p.coordinate().distance_to(center) <= meters)
+            .collect()
+    }
+
+    fn within_bbox(&self, bbox: &BBox) -> Vec<&P> {
+        self.iter().filter(|p| bbox.contains(p.coordinate())).collect()
+    }
+
+    fn nearest(&self, center: &Coordinate, k: usize) -> Vec<(&P, f64)> {
+        nearest(self.iter().enumerate(), center, k)
+    }
+
+    fn index(&self) -> PointIndex<'_, P> {
+        PointIndex::new(self)
+    }
+}
+
+fn nearest<'a, P: Point>(
+    candidates: impl Iterator<Item = (usize, &'a P)>, center: &Coordinate,
+    k: usize,
+) -> Vec<(&'a P, f64)> {
+    let mut found = candidates
+        .map(|(idx, p)| (idx, p, p.coordinate().distance_to(center)))
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| a.2.total_cmp(&b.2).then(a.0.cmp(&b.0)));
+    found.into_iter().take(k).map(|(_, p, d)| (p, d)).collect()
+}
+
+/// A uniform latitude and longitude grid over a slice of points, sized
+/// for a handful of points per cell.
+#[derive(Debug, Clone)]
+pub struct PointIndex<'a, P> {
+    points: &'a [P],
+    /// cell size in degrees
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl<'a, P: Point> PointIndex<'a, P> {
+    pub fn new(points: &'a [P]) -> Self {
+        let (mut south, mut west) = (f64::MAX, f64::MAX);
+        let (mut north, mut east) = (f64::MIN, f64::MIN);
+        for p in points {
+            let (lat, lng) = p.coordinate().latlng();
+            (south, north) = (south.min(lat), north.max(lat));
+            (west, east) = (west.min(lng), east.max(lng));
+        }
+
+        let cells = (points.len() as f64 / 4.0).max(1.0);
+        let cell = if points.is_empty() {
+            1.0
+        } else {
+            let area = (north - south) * (east - west);
+            let span = (north - south).max(east - west);
+            (area / cells).sqrt().max(span / cells).max(1e-6)
+        };
+        let mut index = Self { points, cell, cells: HashMap::new() };
+
+        for (idx, p) in points.iter().enumerate() {
+            let key = index.key(p.coordinate().latlng());
+            index.cells.entry(key).or_default().push(idx);
+        }
+        index
+    }
+
+    fn key(&self, (lat, lng): (f64, f64)) -> (i64, i64) {
+        (
+            ((lat + 90.0) / self.cell).floor() as i64,
+            ((lng + 180.0) / self.cell).floor() as i64,
+        )
+    }
+
+    /// indices of the points in the cells overlapping `bbox`, unsorted
+    fn candidates(&self, bbox: &BBox) -> Vec<usize> {
+        let mut out = Vec::new();
+        for (west, east) in bbox.lng_ranges() {
+            let (y0, x0) = self.key((bbox.south, west));
+            let (y1, x1) = self.key((bbox.north, east));
+            let cells = (y1 - y0 + 1).saturating_mul(x1 - x0 + 1);
+
+            if cells as usize > self.cells.len() {
+                for ((y, x), points) in self.cells.iter() {
+                    if (y0..=y1).contains(y) && (x0..=x1).contains(x) {
+                        out.extend_from_slice(points);
+                    }
+                }
+                continue;
+            }
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    if let Some(points) = self.cells.get(&(y, x)) {
+                        out.extend_from_slice(points);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn collect(
+        &self, mut idxs: Vec<usize>, f: impl Fn(&P) -> bool,
+    ) -> Vec<&'a P> {
+        idxs.sort_unstable();
+        idxs.dedup();
+        idxs.into_iter().map(|i| &self.points[i]).filter(|p| f(p)).collect()
+    }
+
+    /// points within `meters` of `center`, in input order
+    pub fn within_radius(
+        &self, center: &Coordinate, meters: f64,
+    ) -> Vec<&'a P> {
+        let idxs = self.candidates(&BBox::around(center, meters));
+        self.collect(idxs, |p| p.coordinate().distance_to(center) <= meters)
+    }
+
+    /// points inside of `bbox`, in input order
+    pub fn within_bbox(&self, bbox: &BBox) -> Vec<&'a P> {
+        self.collect(self.candidates(bbox), |p| bbox.contains(p.coordinate()))
+    }
+
+    /// the `k` points nearest to `center` with their distance in meters,
+    /// nearest first
+    pub fn nearest(&self, center: &Coordinate, k: usize) -> Vec<(&'a P, f64)> {
+        if k == 0 || self.points.is_empty() {
+            return Vec::new();
+        }
+
+        // grow a ring of cells around the center until it holds k points,
+        // the k-th distance among those bounds the real answer
+        let (cy, cx) = self.key(center.latlng());
+        let mut found = Vec::new();
+        let mut ring = 0i64;
+        while found.len() < k {
+            if (2 * ring + 1).pow(2) > 4 * self.cells.len() as i64 {
+                found = (0..self.points.len()).collect();
+                break;
+            }
+
+            let mut visit = |y, x| {
+                if let Some(points) = self.cells.get(&(y, x)) {
+                    found.extend_from_slice(points);
+                }
+            };
+            if ring == 0 {
+                visit(cy, cx);
+            }
+            for x in (cx - ring..=cx + ring).filter(|_| ring > 0) {
+                visit(cy - ring, x);
+                visit(cy + ring, x);
+            }
+            for y in (cy - ring + 1..cy + ring).filter(|_| ring > 0) {
+                visit(y, cx - ring);
+                visit(y, cx + ring);
+            }
+            ring += 1;
+        }
+
+        let bound =
+            nearest(found.into_iter().map(|i| (i, &self.points[i])), center, k);
+        let Some(&(_, radius)) = bound.last() else { return Vec::new() };
+
+        let idxs = self.candidates(&BBox::around(center, radius));
+        let candidates = idxs.into_iter().map(|i| (i, &self.points[i]));
+        nearest(candidates, center, k)
+    }
+}
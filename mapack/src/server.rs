@@ -0,0 +1,215 @@
+//! Serves the tiles of a [`TileStore`] over HTTP.
+//!
+//! The router can be served with `axum::serve` or nested into a larger app.
+
+use crate::compression::content_encoding;
+use crate::{decompress, TileStore};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+use tower_http::compression::CompressionLayer;
+
+pub const CONTENT_TYPE: &str = "application/vnd.mapbox-vector-tile";
+
+/// Router serving `/{z}/{x}/{y}.pbf`.
+///
+/// Missing and empty tiles are `204 No Content`. tiles are gzipped for
+/// clients accepting it. tiles stored gzip or zstd compressed are sent as
+/// they are to clients accepting that encoding and decompressed for the
+/// rest, zstd needs the `zstd` feature for that.
+pub fn router<S>(store: S) -> Router
+where
+    S: TileStore + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/{z}/{x}/{y}", get(tile::<S>))
+        .layer(CompressionLayer::new().gzip(true))
+        .with_state(Arc::new(store))
+}
+
+/// Weak ETag of the stored tile, a FNV-1a hash so it stays the same across
+/// builds. weak since the same tile is sent with different encodings
+fn etag(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("W/\"{hash:016x}\"")
+}
+
+fn accepts(headers: &HeaderMap, encoding: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| {
+            let mut parts = v.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let refused = parts.any(|p| {
+                p.strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            (name.eq_ignore_ascii_case(encoding) || name == "*") && !refused
+        })
+}
+
+fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim())
+        .any(|v| {
+            v == "*"
+                || v.trim_start_matches("W/") == etag.trim_start_matches("W/")
+        })
+}
+
+async fn tile<S>(
+    State(store): State<Arc<S>>, Path((zom, tx, ty)): Path<(u8, u32, String)>,
+    headers: HeaderMap,
+) -> Response
+where
+    S: TileStore + Send + Sync + 'static,
+{
+    let ty = match ty.strip_suffix(".pbf").map(str::parse::<u32>) {
+        Some(Ok(ty)) => ty,
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    if zom > 31 || tx >> zom != 0 || ty >> zom != 0 {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let data = tokio::task::spawn_blocking(move || store.get(zom, tx, ty))
+        .await
+        .map_err(std::io::Error::other)
+        .and_then(|r| r);
+    let data = match data {
+        Ok(Some(data)) => data,
+        Ok(None) => return StatusCode::NO_CONTENT.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let etag = etag(&data);
+    let mut res = if not_modified(&headers, &etag) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let encoding = content_encoding(&data);
+        let (data, encoding) = match encoding {
+            Some(e) if !accepts(&headers, e) => match decompress(data) {
+                Ok(data) => (data, None),
+                Err(_) => return StatusCode::NOT_ACCEPTABLE.into_response(),
+            },
+            _ => (data, encoding),
+        };
+
+        let mut res = data.into_response();
+        let h = res.headers_mut();
+        h.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+        if let Some(encoding) = encoding {
+            h.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(encoding),
+            );
+        }
+        res
+    };
+
+    let h = res.headers_mut();
+    h.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        h.insert(header::ETAG, etag);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{router, CONTENT_TYPE};
+    use crate::{compress, Compression, MemStore, TileStore};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn request(
+        uri: &str, headers: &[(header::HeaderName, &str)],
+    ) -> Request<Body> {
+        let mut req = Request::get(uri);
+        for (k, v) in headers {
+            req = req.header(k, *v);
+        }
+        req.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serve() {
+        let store = MemStore::default();
+        let tile = vec![7u8; 512];
+        store.put(12, 2621, 1614, &tile).unwrap();
+
+        let stored = compress(tile.clone(), Compression::Gzip).unwrap();
+        store.put(12, 2621, 1613, &stored).unwrap();
+        #[allow(unused_mut)]
+        let mut cases = vec![("gzip", "/12/2621/1613.pbf", stored)];
+        #[cfg(feature = "zstd")]
+        {
+            let stored = compress(tile.clone(), Compression::Zstd).unwrap();
+            store.put(12, 2621, 1612, &stored).unwrap();
+            cases.push(("zstd", "/12/2621/1612.pbf", stored));
+        }
+        let app = router(store);
+
+        let res = app.clone().oneshot(request("/12/2621/1614.pbf", &[])).await;
+        let res = res.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        let etag = res.headers()[header::ETAG].to_str().unwrap().to_string();
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, tile);
+
+        let gzip = [(header::ACCEPT_ENCODING, "gzip")];
+        let res = app.clone().oneshot(request("/12/2621/1614.pbf", &gzip));
+        let res = res.await.unwrap();
+        assert_eq!(res.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(res.headers().get_all(header::VARY).iter().count(), 1);
+        assert_eq!(res.headers()[header::ETAG], etag);
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert!(body.len() < tile.len());
+
+        assert_eq!(etag, super::etag(&tile));
+        assert_eq!(super::etag(b"tile"), "W/\"1e5099ef2e9bcca9\"");
+
+        let cached = [(header::IF_NONE_MATCH, etag.as_str())];
+        let res = app.clone().oneshot(request("/12/2621/1614.pbf", &cached));
+        assert_eq!(res.await.unwrap().status(), StatusCode::NOT_MODIFIED);
+
+        for (encoding, uri, stored) in cases {
+            let accept = [(header::ACCEPT_ENCODING, encoding)];
+            let res = app.clone().oneshot(request(uri, &accept)).await.unwrap();
+            assert_eq!(res.headers()[header::CONTENT_ENCODING], encoding);
+            assert_eq!(res.headers()[header::VARY], "accept-encoding");
+            let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(body, stored);
+
+            let res = app.clone().oneshot(request(uri, &[])).await.unwrap();
+            assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+            let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(body, tile);
+        }
+
+        for (uri, status) in [
+            ("/12/2621/1615.pbf", StatusCode::NO_CONTENT),
+            ("/12/2621/1614.mvt", StatusCode::NOT_FOUND),
+            ("/1/2/0.pbf", StatusCode::NOT_FOUND),
+            ("/a/2/0.pbf", StatusCode::BAD_REQUEST),
+        ] {
+            let res = app.clone().oneshot(request(uri, &[])).await.unwrap();
+            assert_eq!(res.status(), status, "{uri}");
+        }
+    }
+}
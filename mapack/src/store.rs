@@ -0,0 +1,293 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+const STRIPES: usize = 64;
+
+/// Striped in-process mutexes keyed by z/x/y.
+///
+/// Two different tiles may share a stripe, the same tile always maps to
+/// the same one.
+#[derive(Debug)]
+pub struct TileLocks {
+    stripes: Box<[Mutex<()>]>,
+}
+
+impl Default for TileLocks {
+    fn default() -> Self {
+        Self::new(STRIPES)
+    }
+}
+
+impl TileLocks {
+    pub fn new(stripes: usize) -> Self {
+        Self { stripes: (0..stripes.max(1)).map(|_| Mutex::new(())).collect() }
+    }
+
+    pub fn lock(&self, zom: u8, tx: u32, ty: u32) -> MutexGuard<'_, ()> {
+        let mut hasher = DefaultHasher::new();
+        (zom, tx, ty).hash(&mut hasher);
+        let idx = (hasher.finish() % self.stripes.len() as u64) as usize;
+
+        self.stripes[idx].lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Storage of encoded tiles addressed by zoom, tile x and tile y.
+pub trait TileStore {
+    /// returns `None` for missing or empty tiles
+    fn get(&self, zom: u8, tx: u32, ty: u32) -> io::Result<Option<Vec<u8>>>;
+
+    fn put(&self, zom: u8, tx: u32, ty: u32, data: &[u8]) -> io::Result<()>;
+
+    fn remove(&self, zom: u8, tx: u32, ty: u32) -> io::Result<()>;
+
+    /// Read-modify-write of a single tile, serialised against every other
+    /// `update`, `put` and `remove` of the same tile on this store.
+    ///
+    /// `f` gets the current tile (`None` if it is missing or empty) and
+    /// returns the new one, `None` empties the tile. it runs while the
+    /// store holds its lock and must not call back into the store, reading
+    /// any other tile from `f` can deadlock. read them before the update.
+    fn update<F, E>(&self, zom: u8, tx: u32, ty: u32, f: F) -> Result<(), E>
+    where
+        Self: Sized,
+        F: FnOnce(Option<Vec<u8>>) -> Result<Option<Vec<u8>>, E>,
+        E: From<io::Error>;
+}
+
+/// Tiles stored as `{root}/{z}/{x}/{y}.pbf`.
+#[derive(Debug)]
+pub struct DirStore {
+    root: PathBuf,
+    locks: TileLocks,
+    file_locks: bool,
+}
+
+impl DirStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            locks: TileLocks::default(),
+            file_locks: false,
+        }
+    }
+
+    /// Also take an advisory lock on the tile file while writing it.
+    /// needed when more than one process writes into the same directory.
+    pub fn with_file_locks(mut self, enabled: bool) -> Self {
+        self.file_locks = enabled;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, zom: u8, tx: u32, ty: u32) -> PathBuf {
+        let mut path = self.root.join(zom.to_string()).join(tx.to_string());
+        path.push(ty.to_string());
+        path.set_extension("pbf");
+        path
+    }
+
+    fn open(&self, zom: u8, tx: u32, ty: u32) -> io::Result<File> {
+        let path = self.path(zom, tx, ty);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if self.file_locks {
+            file.lock()?;
+        }
+
+        Ok(file)
+    }
+}
+
+impl TileStore for DirStore {
+    fn get(&self, zom: u8, tx: u32, ty: u32) -> io::Result<Option<Vec<u8>>> {
+        let _guard = self.locks.lock(zom, tx, ty);
+        let mut file = match File::open(self.path(zom, tx, ty)) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        if self.file_locks {
+            file.lock_shared()?;
+        }
+
+        let mut data = Vec::<u8>::new();
+        file.read_to_end(&mut data)?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(data))
+    }
+
+    fn put(&self, zom: u8, tx: u32, ty: u32, data: &[u8]) -> io::Result<()> {
+        let _guard = self.locks.lock(zom, tx, ty);
+        let mut file = self.open(zom, tx, ty)?;
+        file.write_all(data)?;
+        file.set_len(data.len() as u64)
+    }
+
+    fn remove(&self, zom: u8, tx: u32, ty: u32) -> io::Result<()> {
+        let _guard = self.locks.lock(zom, tx, ty);
+        if self.file_locks {
+            // other processes may already hold the file open waiting for
+            // the lock, unlinking it would make their writes disappear.
+            return match File::options()
+                .write(true)
+                .open(self.path(zom, tx, ty))
+            {
+                Ok(file) => {
+                    file.lock()?;
+                    file.set_len(0)
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e),
+            };
+        }
+
+        match fs::remove_file(self.path(zom, tx, ty)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            r => r,
+        }
+    }
+
+    fn update<F, E>(&self, zom: u8, tx: u32, ty: u32, f: F) -> Result<(), E>
+    where
+        F: FnOnce(Option<Vec<u8>>) -> Result<Option<Vec<u8>>, E>,
+        E: From<io::Error>,
+    {
+        let _guard = self.locks.lock(zom, tx, ty);
+        let mut file = self.open(zom, tx, ty)?;
+
+        let mut data = Vec::<u8>::new();
+        file.read_to_end(&mut data)?;
+        let data = if data.is_empty() { None } else { Some(data) };
+
+        // the file is only truncated, never unlinked. see `remove`
+        let data = f(data)?.unwrap_or_default();
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&data)?;
+        file.set_len(data.len() as u64)?;
+
+        Ok(())
+    }
+}
+
+/// Tiles kept in a `HashMap`, mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct MemStore {
+    tiles: Mutex<HashMap<(u8, u32, u32), Vec<u8>>>,
+}
+
+impl MemStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tiles(&self) -> MutexGuard<'_, HashMap<(u8, u32, u32), Vec<u8>>> {
+        self.tiles.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TileStore for MemStore {
+    fn get(&self, zom: u8, tx: u32, ty: u32) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.tiles().get(&(zom, tx, ty)).cloned())
+    }
+
+    fn put(&self, zom: u8, tx: u32, ty: u32, data: &[u8]) -> io::Result<()> {
+        self.tiles().insert((zom, tx, ty), data.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, zom: u8, tx: u32, ty: u32) -> io::Result<()> {
+        self.tiles().remove(&(zom, tx, ty));
+        Ok(())
+    }
+
+    fn update<F, E>(&self, zom: u8, tx: u32, ty: u32, f: F) -> Result<(), E>
+    where
+        F: FnOnce(Option<Vec<u8>>) -> Result<Option<Vec<u8>>, E>,
+        E: From<io::Error>,
+    {
+        let mut tiles = self.tiles();
+        match f(tiles.get(&(zom, tx, ty)).cloned())? {
+            Some(data) if !data.is_empty() => {
+                tiles.insert((zom, tx, ty), data);
+            }
+            _ => {
+                tiles.remove(&(zom, tx, ty));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DirStore, MemStore, TileStore};
+    use std::io;
+
+    fn increment(store: &(impl TileStore + Sync)) {
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        store
+                            .update(12, 2621, 1614, |data| {
+                                let n = match data {
+                                    Some(d) => u64::from_le_bytes(
+                                        d.try_into().expect("bad counter"),
+                                    ),
+                                    None => 0,
+                                };
+                                io::Result::Ok(Some(
+                                    (n + 1).to_le_bytes().to_vec(),
+                                ))
+                            })
+                            .expect("update");
+                    }
+                });
+            }
+        });
+
+        let data = store.get(12, 2621, 1614).unwrap().unwrap();
+        assert_eq!(u64::from_le_bytes(data.try_into().unwrap()), 400);
+    }
+
+    #[test]
+    fn concurrent_update() {
+        increment(&MemStore::new());
+
+        for file_locks in [false, true] {
+            let root = std::env::temp_dir().join(format!(
+                "mapack-store-{}-{file_locks}",
+                std::process::id()
+            ));
+            let store = DirStore::new(&root).with_file_locks(file_locks);
+            increment(&store);
+
+            store.remove(12, 2621, 1614).unwrap();
+            assert!(store.get(12, 2621, 1614).unwrap().is_none());
+            let _ = std::fs::remove_dir_all(root);
+        }
+    }
+}
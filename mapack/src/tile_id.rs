@@ -0,0 +1,357 @@
+use crate::coordinate::{world, MERCATOR_EXTENT};
+use crate::query::BBox;
+use crate::Coordinate;
+use core::f64::consts::PI;
+use std::fmt;
+
+/// A tile of the z/x/y scheme, `y` grows to the south.
+///
+/// Nothing stops the fields from being out of range. methods on such a
+/// tile do not panic but give meaningless bounds, see [`TileId::is_valid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileId {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    pub fn new(z: u8, x: u32, y: u32) -> Self {
+        Self { z, x, y }
+    }
+
+    /// number of tiles along each side of the map on zoom `z`, saturates
+    /// past zoom 63
+    fn size(z: u8) -> u64 {
+        1u64.checked_shl(z as u32).unwrap_or(u64::MAX)
+    }
+
+    /// false for tiles past the edge of the map or zoom levels past 32
+    pub fn is_valid(&self) -> bool {
+        self.z <= 32
+            && (self.x as u64) < Self::size(self.z)
+            && (self.y as u64) < Self::size(self.z)
+    }
+
+    /// edges of the tile in degrees
+    pub fn bounds(&self) -> BBox {
+        let n = Self::size(self.z) as f64;
+        let lng = |x: f64| x / n * 360.0 - 180.0;
+        let lat =
+            |y: f64| (PI * (1.0 - 2.0 * y / n)).sinh().atan().to_degrees();
+        let (x, y) = (self.x as f64, self.y as f64);
+
+        BBox::new(lng(x), lat(y + 1.0), lng(x + 1.0), lat(y))
+    }
+
+    /// edges of the tile in Web Mercator (EPSG:3857) meters, as
+    /// `[min_x, min_y, max_x, max_y]`
+    pub fn bounds_meters(&self) -> [f64; 4] {
+        let size = 2.0 * MERCATOR_EXTENT / Self::size(self.z) as f64;
+        let (x, y) = (self.x as f64, self.y as f64);
+        let min_x = x * size - MERCATOR_EXTENT;
+        let max_y = MERCATOR_EXTENT - y * size;
+
+        [min_x, max_y - size, min_x + size, max_y]
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        let z = self.z.checked_sub(1)?;
+        Some(Self::new(z, self.x / 2, self.y / 2))
+    }
+
+    /// The four tiles of the next zoom level, in the order `(2x, 2y)`,
+    /// `(2x + 1, 2y)`, `(2x, 2y + 1)` and `(2x + 1, 2y + 1)`. `None` for
+    /// invalid tiles and zoom 32, whose children would be invalid.
+    pub fn children(&self) -> Option<[Self; 4]> {
+        if !self.is_valid() || self.z >= 32 {
+            return None;
+        }
+
+        let (z, x, y) = (self.z + 1, self.x * 2, self.y * 2);
+        Some([
+            Self::new(z, x, y),
+            Self::new(z, x + 1, y),
+            Self::new(z, x, y + 1),
+            Self::new(z, x + 1, y + 1),
+        ])
+    }
+
+    /// The up to eight tiles around this one. `x` wraps around the
+    /// antimeridian, there is nothing past the top and bottom of the map.
+    /// empty for invalid tiles
+    pub fn neighbours(&self) -> Vec<Self> {
+        if !self.is_valid() {
+            return Vec::new();
+        }
+
+        let n = Self::size(self.z) as i64;
+        let (x, y) = (self.x as i64, self.y as i64);
+
+        let mut out = Vec::with_capacity(8);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let ny = y + dy;
+                if !(0..n).contains(&ny) {
+                    continue;
+                }
+                let tile =
+                    Self::new(self.z, (x + dx).rem_euclid(n) as u32, ny as u32);
+                if tile != *self && !out.contains(&tile) {
+                    out.push(tile);
+                }
+            }
+        }
+        out
+    }
+
+    /// false for every coordinate if the tile is invalid
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+
+        let (lat, lng) = coordinate.latlng();
+        let (x, y) = world(lat, lng);
+        let n = Self::size(self.z) as f64;
+
+        (x * n).floor() == self.x as f64 && (y * n).floor() == self.y as f64
+    }
+
+    /// true for this tile and every tile below it on higher zoom levels
+    pub fn contains_tile(&self, other: &Self) -> bool {
+        let Some(shift) = other.z.checked_sub(self.z) else { return false };
+        let ancestor = |v: u32| v.checked_shr(shift as u32).unwrap_or(0);
+
+        ancestor(other.x) == self.x && ancestor(other.y) == self.y
+    }
+
+    /// Row of the tile in the TMS scheme, where `y` grows to the north.
+    /// flipping twice gives back the same row, `None` for invalid tiles
+    pub fn tms_y(&self) -> Option<u32> {
+        let n = Self::size(self.z);
+        self.is_valid().then(|| (n - 1 - self.y as u64) as u32)
+    }
+
+    /// tile from a TMS row, see [`TileId::tms_y`]
+    pub fn from_tms(z: u8, x: u32, tms_y: u32) -> Option<Self> {
+        let y = Self::new(z, x, tms_y).tms_y()?;
+        Some(Self::new(z, x, y))
+    }
+
+    /// Bing Maps quadkey, one digit per zoom level. zoom 0 is the empty
+    /// string
+    pub fn quadkey(&self) -> String {
+        (1..=self.z)
+            .rev()
+            .map(|i| {
+                let bit = |v: u32| v.checked_shr(i as u32 - 1).unwrap_or(0) & 1;
+                char::from(b'0' + (bit(self.x) | bit(self.y) << 1) as u8)
+            })
+            .collect()
+    }
+
+    /// `None` for keys longer than 32 digits or with digits other than
+    /// `0` to `3`
+    pub fn from_quadkey(quadkey: &str) -> Option<Self> {
+        if quadkey.len() > 32 {
+            return None;
+        }
+
+        let mut tile = Self::new(0, 0, 0);
+        for digit in quadkey.bytes() {
+            let digit = digit.checked_sub(b'0').filter(|d| *d < 4)? as u32;
+            tile = Self::new(
+                tile.z + 1,
+                tile.x << 1 | digit & 1,
+                tile.y << 1 | digit >> 1,
+            );
+        }
+        Some(tile)
+    }
+
+    /// Packs the tile into 64 bits as `z << 58 | x << 29 | y`. packed ids
+    /// sort by zoom first, `None` for invalid tiles or zoom levels past 29
+    pub fn to_packed(&self) -> Option<u64> {
+        if self.z > 29 || !self.is_valid() {
+            return None;
+        }
+        Some((self.z as u64) << 58 | (self.x as u64) << 29 | self.y as u64)
+    }
+
+    /// reverse of [`TileId::to_packed`]
+    pub fn from_packed(packed: u64) -> Option<Self> {
+        let mask = (1 << 29) - 1;
+        let tile = Self::new(
+            (packed >> 58) as u8,
+            (packed >> 29 & mask) as u32,
+            (packed & mask) as u32,
+        );
+        tile.to_packed().filter(|p| *p == packed).map(|_| tile)
+    }
+
+    /// Tiles of zoom `z` overlapping `bbox`, row by row from the north
+    /// west corner. a box crossing the antimeridian is split in two, zoom
+    /// levels past 32 have no tiles.
+    pub fn tiles_covering(bbox: &BBox, z: u8) -> impl Iterator<Item = Self> {
+        // a box ending on the edge of a tile does not reach into the next
+        // one, with some slack for the rounding of the projection
+        let n = Self::size(z) as f64;
+        let first = |v: f64| (v * n + 1e-9).floor().clamp(0.0, n - 1.0);
+        let last = |v: f64| (v * n - 1e-9).ceil().clamp(1.0, n) - 1.0;
+        let range = |v0: f64, v1: f64| {
+            let v0 = first(v0);
+            (v0 as u32, last(v1).max(v0) as u32)
+        };
+
+        let (y0, y1) = match z {
+            0..=32 => range(world(bbox.north, 0.0).1, world(bbox.south, 0.0).1),
+            _ => (1, 0),
+        };
+        let columns = bbox
+            .lng_ranges()
+            .map(|(west, east)| range(world(0.0, west).0, world(0.0, east).0))
+            .collect::<Vec<_>>();
+
+        (y0..=y1).flat_map(move |y| {
+            columns.clone().into_iter().flat_map(move |(x0, x1)| {
+                (x0..=x1).map(move |x| Self::new(z, x, y))
+            })
+        })
+    }
+}
+
+impl From<&Coordinate> for TileId {
+    fn from(coordinate: &Coordinate) -> Self {
+        let (x, y) = coordinate.txy();
+        Self::new(coordinate.zoom(), x, y)
+    }
+}
+
+impl fmt::Display for TileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.z, self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TileId;
+    use crate::query::BBox;
+    use crate::Coordinate;
+
+    #[test]
+    fn tile_id() {
+        let c =
+            Coordinate::from_latlng(12, 35.55280736473704, 50.38718834763207);
+        let tile = TileId::from(&c);
+        assert_eq!(tile, TileId::new(12, 2621, 1614));
+        assert_eq!(tile.to_string(), "12/2621/1614");
+        assert!(tile.is_valid());
+        assert!(tile.contains(&c));
+
+        let b = tile.bounds();
+        let (lat, lng) = c.latlng();
+        assert!(
+            b.west <= lng && lng < b.east && b.south < lat && lat <= b.north
+        );
+        let corner = Coordinate::from_screen(12, 2621, 1614, 0, 4096);
+        assert!((corner.latitude() - b.north).abs() < 1e-9);
+        assert!((corner.longitude() - b.west).abs() < 1e-9);
+
+        let m = TileId::new(0, 0, 0).bounds_meters();
+        assert_eq!(
+            m,
+            [
+                -20037508.342789244,
+                -20037508.342789244,
+                20037508.342789244,
+                20037508.342789244
+            ]
+        );
+        let m = TileId::new(1, 1, 0).bounds_meters();
+        assert_eq!(m, [0.0, 0.0, 20037508.342789244, 20037508.342789244]);
+
+        let parent = tile.parent().unwrap();
+        assert_eq!(parent, TileId::new(11, 1310, 807));
+        assert!(parent.contains_tile(&tile));
+        assert!(!tile.contains_tile(&parent));
+        assert_eq!(TileId::new(0, 0, 0).parent(), None);
+        assert!(TileId::new(0, 0, 0).contains_tile(&tile));
+        for child in tile.children().unwrap() {
+            assert_eq!(child.parent(), Some(tile));
+        }
+        assert_eq!(
+            tile.children().unwrap().iter().filter(|t| t.contains(&c)).count(),
+            1
+        );
+
+        assert_eq!(tile.neighbours().len(), 8);
+        assert!(TileId::new(0, 0, 0).neighbours().is_empty());
+        let edge = TileId::new(2, 0, 0).neighbours();
+        assert_eq!(edge.len(), 5);
+        assert!(edge.contains(&TileId::new(2, 3, 1)));
+
+        let covering = TileId::tiles_covering(&b, 12).collect::<Vec<_>>();
+        assert_eq!(covering, [tile]);
+        let covering = TileId::tiles_covering(&b, 13).collect::<Vec<_>>();
+        assert_eq!(covering.len(), 4);
+        assert!(covering.iter().all(|t| tile.contains_tile(t)));
+
+        assert_eq!(tile.tms_y(), Some(4095 - 1614));
+        assert_eq!(TileId::from_tms(12, 2621, 4095 - 1614), Some(tile));
+        assert_eq!(TileId::new(0, 0, 0).tms_y(), Some(0));
+        assert_eq!(TileId::new(2, 1, 4).tms_y(), None);
+        assert_eq!(TileId::from_tms(2, 1, 4), None);
+
+        assert_eq!(TileId::new(3, 3, 5).quadkey(), "213");
+        assert_eq!(TileId::new(0, 0, 0).quadkey(), "");
+        assert_eq!(TileId::from_quadkey("213"), Some(TileId::new(3, 3, 5)));
+        assert_eq!(TileId::from_quadkey(&tile.quadkey()), Some(tile));
+        assert_eq!(TileId::from_quadkey("214"), None);
+        assert_eq!(TileId::from_quadkey(&"3".repeat(33)), None);
+
+        let packed = tile.to_packed().unwrap();
+        assert_eq!(TileId::from_packed(packed), Some(tile));
+        assert!(packed > TileId::new(11, 2047, 2047).to_packed().unwrap());
+        assert_eq!(TileId::new(30, 0, 0).to_packed(), None);
+        assert_eq!(TileId::new(2, 4, 0).to_packed(), None);
+        assert_eq!(TileId::from_packed(2 << 58 | 7 << 29), None);
+
+        // out of range tiles do not overflow
+        for tile in [
+            TileId::new(255, u32::MAX, u32::MAX),
+            TileId::new(64, 0, 0),
+            TileId::new(33, 0, 0),
+            TileId::new(2, 4, 0),
+        ] {
+            assert!(!tile.is_valid());
+            assert_eq!(tile.children(), None);
+            assert!(tile.neighbours().is_empty());
+            assert!(!tile.contains(&c));
+            tile.bounds();
+            tile.bounds_meters();
+        }
+        let last = TileId::new(32, u32::MAX, u32::MAX);
+        assert!(last.is_valid());
+        assert_eq!(last.children(), None);
+        assert_eq!(
+            TileId::new(31, u32::MAX >> 1, 0).children().unwrap()[1].x,
+            u32::MAX
+        );
+        assert_eq!(TileId::tiles_covering(&b, 33).count(), 0);
+        assert_eq!(TileId::tiles_covering(&b, 255).count(), 0);
+
+        let dateline = BBox::new(170.0, -10.0, -170.0, 10.0);
+        let covering = TileId::tiles_covering(&dateline, 3).collect::<Vec<_>>();
+        assert_eq!(
+            covering,
+            [
+                TileId::new(3, 7, 3),
+                TileId::new(3, 0, 3),
+                TileId::new(3, 7, 4),
+                TileId::new(3, 0, 4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use serde_json::{json, Map, Value as Json};
+
+/// A layer of a `mapack!` schema, see the generated `Tile::VECTOR_LAYERS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorLayer {
+    pub id: &'static str,
+    /// key and type ("String", "Number" or "Boolean") of every field
+    pub fields: &'static [(&'static str, &'static str)],
+    /// `None` uses the zoom range of the tileset
+    pub minzoom: Option<u8>,
+    pub maxzoom: Option<u8>,
+}
+
+/// Tileset metadata rendered into a TileJSON 3.0 document.
+#[derive(Debug, Clone)]
+pub struct TileJson {
+    tiles: Vec<String>,
+    name: Option<String>,
+    description: Option<String>,
+    attribution: Option<String>,
+    version: Option<String>,
+    minzoom: u8,
+    maxzoom: u8,
+    bounds: Option<[f64; 4]>,
+    center: Option<[f64; 3]>,
+}
+
+impl TileJson {
+    /// `tiles` are url templates such as `https://example.com/{z}/{x}/{y}.pbf`
+    pub fn new<S: Into<String>>(tiles: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            tiles: tiles.into_iter().map(Into::into).collect(),
+            name: None,
+            description: None,
+            attribution: None,
+            version: None,
+            minzoom: 0,
+            maxzoom: 22,
+            bounds: None,
+            center: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_attribution(mut self, attribution: &str) -> Self {
+        self.attribution = Some(attribution.to_string());
+        self
+    }
+
+    /// semver version of the tileset
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    pub fn with_zoom(mut self, minzoom: u8, maxzoom: u8) -> Self {
+        self.minzoom = minzoom.min(maxzoom);
+        self.maxzoom = maxzoom.max(minzoom);
+        self
+    }
+
+    /// west, south, east and north in degrees
+    pub fn with_bounds(mut self, bounds: [f64; 4]) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// longitude, latitude and zoom
+    pub fn with_center(mut self, center: [f64; 3]) -> Self {
+        self.center = Some(center);
+        self
+    }
+
+    /// Layer zoom ranges are clamped to the zoom range of the tileset.
+    pub fn to_json(&self, layers: &[VectorLayer]) -> Json {
+        let clamp = |z: Option<u8>, default: u8| {
+            z.unwrap_or(default).clamp(self.minzoom, self.maxzoom)
+        };
+
+        let vector_layers = layers
+            .iter()
+            .map(|l| {
+                let fields = l
+                    .fields
+                    .iter()
+                    .map(|(k, t)| (k.to_string(), json!(t)))
+                    .collect::<Map<_, _>>();
+                json!({
+                    "id": l.id,
+                    "fields": fields,
+                    "minzoom": clamp(l.minzoom, self.minzoom),
+                    "maxzoom": clamp(l.maxzoom, self.maxzoom),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut doc = json!({
+            "tilejson": "3.0.0",
+            "tiles": self.tiles,
+            "vector_layers": vector_layers,
+            "minzoom": self.minzoom,
+            "maxzoom": self.maxzoom,
+        });
+
+        let optional = [
+            ("name", self.name.as_ref().map(|v| json!(v))),
+            ("description", self.description.as_ref().map(|v| json!(v))),
+            ("attribution", self.attribution.as_ref().map(|v| json!(v))),
+            ("version", self.version.as_ref().map(|v| json!(v))),
+            ("bounds", self.bounds.map(|v| json!(v))),
+            ("center", self.center.map(|v| json!(v))),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                doc[key] = value;
+            }
+        }
+
+        doc
+    }
+}
@@ -0,0 +1,376 @@
+use crate::geometry::{commands, decode_paths, CLOSE_PATH, LINE_TO, MOVE_TO};
+use crate::{GeomType, Layer, Tile};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A rule of the MVT 2.1 spec broken by a tile, see [`Rule::is_warning`]
+/// for the ones the spec only recommends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// layer version is not 2
+    Version(u32),
+    EmptyName,
+    DuplicateLayer,
+    MissingExtent,
+    DuplicateKey(String),
+    /// value at this index of the values table does not have exactly one
+    /// field set
+    BadValue(usize),
+    OddTags,
+    /// key or value index of a tag pair is out of range
+    TagOutOfRange {
+        key: u32,
+        value: u32,
+    },
+    /// same key is tagged more than once on one feature
+    DuplicateTag(String),
+    /// ids SHOULD be unique, a warning
+    DuplicateId(u64),
+    MissingType,
+    /// allowed and left to the decoder, a warning
+    UnknownType,
+    /// geometry command integers are malformed
+    Geometry(&'static str),
+    /// commands do not match the geometry type
+    TypeMismatch(GeomType),
+    /// a `LineTo` of a line or a polygon does not move
+    ZeroLengthSegment,
+    /// ring at this index of a polygon has an area of zero, it is neither
+    /// an exterior nor an interior ring
+    ZeroAreaRing(usize),
+    /// first ring of a polygon winds counter-clockwise, as an interior
+    /// ring, instead of clockwise
+    InteriorFirst,
+}
+
+impl Rule {
+    /// true for what the spec only recommends, a tile breaking nothing
+    /// else is still valid
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Self::DuplicateId(_) | Self::UnknownType)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(v) => write!(f, "version is {v} and not 2"),
+            Self::EmptyName => f.write_str("layer name is empty"),
+            Self::DuplicateLayer => f.write_str("layer name is not unique"),
+            Self::MissingExtent => f.write_str("extent is missing or zero"),
+            Self::DuplicateKey(k) => write!(f, "key {k:?} is not unique"),
+            Self::BadValue(i) => {
+                write!(f, "value {i} does not have exactly one field set")
+            }
+            Self::OddTags => f.write_str("odd number of tags"),
+            Self::TagOutOfRange { key, value } => {
+                write!(f, "tag pair {key}, {value} is out of range")
+            }
+            Self::DuplicateTag(k) => write!(f, "key {k:?} is tagged twice"),
+            Self::DuplicateId(id) => write!(f, "id {id} is not unique"),
+            Self::MissingType => f.write_str("geometry type is missing"),
+            Self::UnknownType => f.write_str("geometry type is unknown"),
+            Self::Geometry(e) => write!(f, "bad geometry: {e}"),
+            Self::TypeMismatch(t) => {
+                write!(f, "geometry does not match its type {t:?}")
+            }
+            Self::ZeroLengthSegment => f.write_str("LineTo of zero length"),
+            Self::ZeroAreaRing(i) => write!(f, "ring {i} has no area"),
+            Self::InteriorFirst => {
+                f.write_str("polygon starts with an interior ring")
+            }
+        }
+    }
+}
+
+/// Where in a tile a [`Rule`] is broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// index of the layer in the tile
+    pub layer: usize,
+    pub layer_name: String,
+    /// index of the feature in the layer, `None` for layer wide rules
+    pub feature: Option<usize>,
+    pub rule: Rule,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "layer {} {:?}", self.layer, self.layer_name)?;
+        if let Some(feature) = self.feature {
+            write!(f, " feature {feature}")?;
+        }
+        write!(f, ": {}", self.rule)
+    }
+}
+
+/// Checks a tile against every rule of the MVT 2.1 spec. the tile is valid
+/// when every violation is a warning, see [`Rule::is_warning`].
+pub fn validate(tile: &Tile) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut names = HashSet::<&str>::new();
+
+    for (idx, layer) in tile.layers.iter().enumerate() {
+        let mut violation = |feature: Option<usize>, rule: Rule| {
+            violations.push(Violation {
+                layer: idx,
+                layer_name: layer.name().to_string(),
+                feature,
+                rule,
+            })
+        };
+
+        if !names.insert(layer.name()) {
+            violation(None, Rule::DuplicateLayer);
+        }
+        validate_layer(layer, &mut violation);
+    }
+
+    violations
+}
+
+fn validate_layer(
+    layer: &Layer, violation: &mut impl FnMut(Option<usize>, Rule),
+) {
+    if layer.version() != 2 {
+        violation(None, Rule::Version(layer.version()));
+    }
+    if layer.name().is_empty() {
+        violation(None, Rule::EmptyName);
+    }
+    if layer.extent.unwrap_or(0) == 0 {
+        violation(None, Rule::MissingExtent);
+    }
+
+    let mut keys = HashSet::<&str>::new();
+    for key in layer.keys.iter() {
+        if !keys.insert(key) {
+            violation(None, Rule::DuplicateKey(key.clone()));
+        }
+    }
+
+    for (idx, value) in layer.values.iter().enumerate() {
+        let fields = [
+            value.string_value.is_some(),
+            value.float_value.is_some(),
+            value.double_value.is_some(),
+            value.int_value.is_some(),
+            value.uint_value.is_some(),
+            value.sint_value.is_some(),
+            value.bool_value.is_some(),
+        ];
+        if fields.iter().filter(|f| **f).count() != 1 {
+            violation(None, Rule::BadValue(idx));
+        }
+    }
+
+    let mut ids = HashSet::<u64>::new();
+    for (idx, feature) in layer.features.iter().enumerate() {
+        let mut violation = |rule: Rule| violation(Some(idx), rule);
+
+        if let Some(id) = feature.id {
+            if !ids.insert(id) {
+                violation(Rule::DuplicateId(id));
+            }
+        }
+
+        if feature.tags.len() % 2 != 0 {
+            violation(Rule::OddTags);
+        }
+        let mut tagged = HashSet::<u32>::new();
+        for kv in feature.tags.chunks_exact(2) {
+            let (key, value) = (kv[0], kv[1]);
+            if key as usize >= layer.keys.len()
+                || value as usize >= layer.values.len()
+            {
+                violation(Rule::TagOutOfRange { key, value });
+            } else if !tagged.insert(key) {
+                violation(Rule::DuplicateTag(layer.keys[key as usize].clone()));
+            }
+        }
+
+        let geom_type = match feature.geom_type {
+            None => {
+                violation(Rule::MissingType);
+                continue;
+            }
+            Some(GeomType::Unknown) => {
+                violation(Rule::UnknownType);
+                continue;
+            }
+            Some(t) => t,
+        };
+
+        match commands(&feature.geometry) {
+            Ok(commands) if !matches_type(geom_type, &commands) => {
+                violation(Rule::TypeMismatch(geom_type))
+            }
+            Ok(_) => validate_paths(geom_type, &feature.geometry, violation),
+            Err(e) => violation(Rule::Geometry(e)),
+        }
+    }
+}
+
+/// rules on the positions of lines and polygons, the commands already
+/// match the type
+fn validate_paths(
+    geom_type: GeomType, geometry: &[u32], mut violation: impl FnMut(Rule),
+) {
+    if geom_type == GeomType::Point {
+        return;
+    }
+
+    let paths = decode_paths(geometry);
+    if paths.iter().any(|p| p.windows(2).any(|w| w[0] == w[1])) {
+        violation(Rule::ZeroLengthSegment);
+    }
+    if geom_type != GeomType::Polygon {
+        return;
+    }
+
+    for (idx, ring) in paths.iter().enumerate() {
+        // surveyor's formula, positive for clockwise rings as y grows
+        // downwards in a tile
+        let area = ring.iter().zip(ring.iter().cycle().skip(1)).fold(
+            0i128,
+            |sum, (&(x0, y0), &(x1, y1))| {
+                let (x0, y0, x1, y1) =
+                    (x0 as i128, y0 as i128, x1 as i128, y1 as i128);
+                sum.wrapping_add(x0 * y1 - x1 * y0)
+            },
+        );
+        if area == 0 {
+            violation(Rule::ZeroAreaRing(idx));
+        } else if idx == 0 && area < 0 {
+            violation(Rule::InteriorFirst);
+        }
+    }
+}
+
+fn matches_type(geom_type: GeomType, commands: &[(u32, u32)]) -> bool {
+    match geom_type {
+        GeomType::Point => matches!(commands, [(MOVE_TO, _)]),
+        GeomType::LineString => {
+            !commands.is_empty()
+                && commands.len().is_multiple_of(2)
+                && commands
+                    .chunks_exact(2)
+                    .all(|c| matches!(c, [(MOVE_TO, 1), (LINE_TO, _)]))
+        }
+        GeomType::Polygon => {
+            !commands.is_empty()
+                && commands.len().is_multiple_of(3)
+                && commands.chunks_exact(3).all(|c| match c {
+                    [(MOVE_TO, 1), (LINE_TO, n), (CLOSE_PATH, 1)] => *n >= 2,
+                    _ => false,
+                })
+        }
+        GeomType::Unknown => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, Rule};
+    use crate::{Coordinate, GeomType, LayerBuilder, Tile, Value};
+
+    #[test]
+    fn rules() {
+        let c =
+            Coordinate::from_latlng(12, 35.55280736473704, 50.38718834763207);
+        let mut poi = LayerBuilder::new("poi");
+        poi.push(Some(1), &c, [("name", Value::from_string("a".into()))]);
+        poi.push(Some(2), &c, [("name", Value::from_string("b".into()))]);
+        poi.push_geometry(
+            Some(3),
+            GeomType::Polygon,
+            vec![9, 0, 0, 26, 20, 0, 0, 20, 19, 0, 15],
+            [("name", Value::from_bool(true))],
+        );
+
+        let mut tile = Tile { layers: vec![poi.build()] };
+        assert_eq!(validate(&tile), vec![]);
+
+        let mut bad = tile.layers[0].clone();
+        bad.version = Some(1);
+        bad.extent = None;
+        bad.keys.push(String::from("name"));
+        bad.values.push(Value::default());
+        bad.features[0].id = Some(2);
+        bad.features[0].tags = vec![0, 0, 0, 1, 5];
+        bad.features[1].geometry = vec![9, 0];
+        bad.features[2].geom_type = None;
+        tile.layers.push(bad.clone());
+        bad.name = Some(String::new());
+        bad.version = Some(2);
+        bad.extent = Some(4096);
+        bad.keys.pop();
+        bad.values.pop();
+        bad.features = bad.features[1..2].to_vec();
+        bad.features[0].geometry = vec![10, 2, 2];
+        tile.layers.push(bad);
+
+        let rules = validate(&tile)
+            .into_iter()
+            .map(|v| (v.layer, v.feature, v.rule))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (1, None, Rule::DuplicateLayer),
+                (1, None, Rule::Version(1)),
+                (1, None, Rule::MissingExtent),
+                (1, None, Rule::DuplicateKey(String::from("name"))),
+                (1, None, Rule::BadValue(3)),
+                (1, Some(0), Rule::OddTags),
+                (1, Some(0), Rule::DuplicateTag(String::from("name"))),
+                (1, Some(1), Rule::DuplicateId(2)),
+                (1, Some(1), Rule::Geometry("missing command parameters")),
+                (1, Some(2), Rule::MissingType),
+                (2, None, Rule::EmptyName),
+                (2, Some(0), Rule::TypeMismatch(GeomType::Point)),
+            ]
+        );
+
+        let mut warned = tile.layers[0].clone();
+        warned.features[1].id = Some(1);
+        warned.features[2].geom_type = Some(GeomType::Unknown);
+        let rules = validate(&Tile { layers: vec![warned] })
+            .into_iter()
+            .map(|v| v.rule)
+            .collect::<Vec<_>>();
+        assert_eq!(rules, vec![Rule::DuplicateId(1), Rule::UnknownType]);
+        assert!(rules.iter().all(Rule::is_warning));
+        assert!(!Rule::MissingType.is_warning());
+
+        let mut shapes = LayerBuilder::new("shapes");
+        let mut push = |id, geom_type, geometry| {
+            let tags = [("name", Value::from_bool(true))];
+            shapes.push_geometry(Some(id), geom_type, geometry, tags);
+        };
+        // a square with a hole
+        push(
+            1,
+            GeomType::Polygon,
+            vec![
+                9, 0, 0, 26, 20, 0, 0, 20, 19, 0, 15, 9, 4, 15, 26, 0, 12, 12,
+                0, 0, 11, 15,
+            ],
+        );
+        push(2, GeomType::LineString, vec![9, 0, 0, 18, 0, 0, 2, 2]);
+        push(3, GeomType::Polygon, vec![9, 0, 0, 26, 0, 20, 20, 0, 0, 19, 15]);
+        push(4, GeomType::Polygon, vec![9, 0, 0, 18, 20, 0, 20, 0, 15]);
+        let rules = validate(&Tile { layers: vec![shapes.build()] })
+            .into_iter()
+            .map(|v| (v.feature, v.rule))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (Some(1), Rule::ZeroLengthSegment),
+                (Some(2), Rule::InteriorFirst),
+                (Some(3), Rule::ZeroAreaRing(0)),
+            ]
+        );
+    }
+}
@@ -1,936 +1,744 @@
-// This file is generated by rust-protobuf 3.7.2. Do not edit
-// .proto file is parsed by protoc --rs_out=...
-// @generated
-
-// https://github.com/rust-lang/rust-clippy/issues/702
-#![allow(unknown_lints)]
-#![allow(clippy::all)]
-
-#![allow(unused_attributes)]
-#![cfg_attr(rustfmt, rustfmt::skip)]
-
-#![allow(dead_code)]
-#![allow(missing_docs)]
-#![allow(non_camel_case_types)]
-#![allow(non_snake_case)]
-#![allow(non_upper_case_globals)]
-#![allow(trivial_casts)]
-#![allow(unused_results)]
-#![allow(unused_mut)]
-
-//! Generated file from `vector_tile.proto`
-// Generated for lite runtime
-
-/// Generated files are compatible only with the same version
-/// of protobuf runtime.
-const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_3_7_2;
-
-// @@protoc_insertion_point(message:vector_tile.Tile)
-#[derive(PartialEq,Clone,Default,Debug)]
+//! the four messages of the MVT 2.1 `vector_tile.proto` and their protobuf
+//! wire format, hand-written. nothing is generated from the spec's schema
+
+use crate::geometry::decode_points;
+use crate::{Coordinate, Error, Point};
+use std::io::{self, Write};
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const FIXED32: u8 = 5;
+
+/// A decoded tile.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Tile {
-    // message fields
-    // @@protoc_insertion_point(field:vector_tile.Tile.layers)
-    pub layers: ::std::vec::Vec<tile::Layer>,
-    // special fields
-    // @@protoc_insertion_point(special_field:vector_tile.Tile.special_fields)
-    pub special_fields: ::protobuf::SpecialFields,
+    pub layers: Vec<Layer>,
 }
 
-impl<'a> ::std::default::Default for &'a Tile {
-    fn default() -> &'a Tile {
-        <Tile as ::protobuf::Message>::default_instance()
-    }
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layer {
+    pub version: Option<u32>,
+    pub name: Option<String>,
+    pub features: Vec<Feature>,
+    pub keys: Vec<String>,
+    pub values: Vec<Value>,
+    pub extent: Option<u32>,
 }
 
-impl Tile {
-    pub fn new() -> Tile {
-        ::std::default::Default::default()
-    }
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Feature {
+    pub id: Option<u64>,
+    /// pairs of key and value indices
+    pub tags: Vec<u32>,
+    pub geom_type: Option<GeomType>,
+    /// geometry command integers
+    pub geometry: Vec<u32>,
 }
 
-impl ::protobuf::Message for Tile {
-    const NAME: &'static str = "Tile";
+/// Exactly one field should be set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Value {
+    pub string_value: Option<String>,
+    pub float_value: Option<f32>,
+    pub double_value: Option<f64>,
+    pub int_value: Option<i64>,
+    pub uint_value: Option<u64>,
+    pub sint_value: Option<i64>,
+    pub bool_value: Option<bool>,
+}
 
-    fn is_initialized(&self) -> bool {
-        for v in &self.layers {
-            if !v.is_initialized() {
-                return false;
-            }
-        };
-        true
-    }
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GeomType {
+    /// also used for values outside of the spec
+    #[default]
+    Unknown,
+    Point,
+    LineString,
+    Polygon,
+}
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
-        while let Some(tag) = is.read_raw_tag_or_eof()? {
-            match tag {
-                26 => {
-                    self.layers.push(is.read_message()?);
-                },
-                tag => {
-                    ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
-                },
-            };
+impl GeomType {
+    fn from_u64(v: u64) -> Self {
+        match v {
+            1 => Self::Point,
+            2 => Self::LineString,
+            3 => Self::Polygon,
+            _ => Self::Unknown,
         }
-        ::std::result::Result::Ok(())
     }
 
-    // Compute sizes of nested messages
-    #[allow(unused_variables)]
-    fn compute_size(&self) -> u64 {
-        let mut my_size = 0;
-        for value in &self.layers {
-            let len = value.compute_size();
-            my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
-        };
-        my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
-        self.special_fields.cached_size().set(my_size as u32);
-        my_size
+    fn to_u64(self) -> u64 {
+        match self {
+            Self::Unknown => 0,
+            Self::Point => 1,
+            Self::LineString => 2,
+            Self::Polygon => 3,
+        }
     }
+}
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
-        for v in &self.layers {
-            ::protobuf::rt::write_message_field_with_cached_size(3, v, os)?;
-        };
-        os.write_unknown_fields(self.special_fields.unknown_fields())?;
-        ::std::result::Result::Ok(())
+impl Layer {
+    /// `1` if missing, as in the spec
+    pub fn version(&self) -> u32 {
+        self.version.unwrap_or(1)
     }
 
-    fn special_fields(&self) -> &::protobuf::SpecialFields {
-        &self.special_fields
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("")
     }
 
-    fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
-        &mut self.special_fields
+    /// `4096` if missing, as in the spec
+    pub fn extent(&self) -> u32 {
+        self.extent.unwrap_or(4096)
     }
 
-    fn new() -> Tile {
-        Tile::new()
-    }
+    /// Positions of a `Point` feature of this layer, scaled from the extent
+    /// of the layer to 4096. `None` for other types, malformed geometries
+    /// and points outside of the tile.
+    pub fn points(
+        &self, zom: u8, tx: u32, ty: u32, feature: &Feature,
+    ) -> Option<Vec<Coordinate>> {
+        if feature.geom_type() != GeomType::Point {
+            return None;
+        }
+
+        let extent = self.extent().max(1) as i64;
+        let point = |(x, y): (i64, i64)| {
+            if !(0..=extent).contains(&x) || !(0..=extent).contains(&y) {
+                return None;
+            }
+            let (x, y) = (x * 4096 / extent, y * 4096 / extent);
+            Some(Coordinate::from_screen(
+                zom,
+                tx,
+                ty,
+                x as u32,
+                4096 - y as u32,
+            ))
+        };
 
-    fn clear(&mut self) {
-        self.layers.clear();
-        self.special_fields.clear();
+        decode_points(&feature.geometry)?.into_iter().map(point).collect()
     }
+}
 
-    fn default_instance() -> &'static Tile {
-        static instance: Tile = Tile {
-            layers: ::std::vec::Vec::new(),
-            special_fields: ::protobuf::SpecialFields::new(),
-        };
-        &instance
+impl Feature {
+    pub fn geom_type(&self) -> GeomType {
+        self.geom_type.unwrap_or_default()
     }
 }
 
-/// Nested message and enums of message `Tile`
-pub mod tile {
-    ///  Variant type encoding
-    ///  The use of values is described in section 4.1 of the specification
-    // @@protoc_insertion_point(message:vector_tile.Tile.Value)
-    #[derive(PartialEq,Clone,Default,Debug)]
-    pub struct Value {
-        // message fields
-        ///  Exactly one of these values must be present in a valid message
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.string_value)
-        pub string_value: ::std::option::Option<::std::string::String>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.float_value)
-        pub float_value: ::std::option::Option<f32>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.double_value)
-        pub double_value: ::std::option::Option<f64>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.int_value)
-        pub int_value: ::std::option::Option<i64>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.uint_value)
-        pub uint_value: ::std::option::Option<u64>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.sint_value)
-        pub sint_value: ::std::option::Option<i64>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Value.bool_value)
-        pub bool_value: ::std::option::Option<bool>,
-        // special fields
-        // @@protoc_insertion_point(special_field:vector_tile.Tile.Value.special_fields)
-        pub special_fields: ::protobuf::SpecialFields,
-    }
-
-    impl<'a> ::std::default::Default for &'a Value {
-        fn default() -> &'a Value {
-            <Value as ::protobuf::Message>::default_instance()
-        }
-    }
-
-    impl Value {
-        pub fn new() -> Value {
-            ::std::default::Default::default()
-        }
-
-        // optional string string_value = 1;
-
-        pub fn string_value(&self) -> &str {
-            match self.string_value.as_ref() {
-                Some(v) => v,
-                None => "",
-            }
-        }
-
-        pub fn clear_string_value(&mut self) {
-            self.string_value = ::std::option::Option::None;
-        }
-
-        pub fn has_string_value(&self) -> bool {
-            self.string_value.is_some()
-        }
-
-        // Param is passed by value, moved
-        pub fn set_string_value(&mut self, v: ::std::string::String) {
-            self.string_value = ::std::option::Option::Some(v);
-        }
-
-        // Mutable pointer to the field.
-        // If field is not initialized, it is initialized with default value first.
-        pub fn mut_string_value(&mut self) -> &mut ::std::string::String {
-            if self.string_value.is_none() {
-                self.string_value = ::std::option::Option::Some(::std::string::String::new());
-            }
-            self.string_value.as_mut().unwrap()
-        }
-
-        // Take field
-        pub fn take_string_value(&mut self) -> ::std::string::String {
-            self.string_value.take().unwrap_or_else(|| ::std::string::String::new())
-        }
-
-        // optional float float_value = 2;
-
-        pub fn float_value(&self) -> f32 {
-            self.float_value.unwrap_or(0.)
-        }
+impl Value {
+    pub fn string_value(&self) -> &str {
+        self.string_value.as_deref().unwrap_or("")
+    }
 
-        pub fn clear_float_value(&mut self) {
-            self.float_value = ::std::option::Option::None;
-        }
+    pub fn float_value(&self) -> f32 {
+        self.float_value.unwrap_or(0.0)
+    }
 
-        pub fn has_float_value(&self) -> bool {
-            self.float_value.is_some()
-        }
+    pub fn double_value(&self) -> f64 {
+        self.double_value.unwrap_or(0.0)
+    }
 
-        // Param is passed by value, moved
-        pub fn set_float_value(&mut self, v: f32) {
-            self.float_value = ::std::option::Option::Some(v);
-        }
+    pub fn int_value(&self) -> i64 {
+        self.int_value.unwrap_or(0)
+    }
 
-        // optional double double_value = 3;
+    pub fn uint_value(&self) -> u64 {
+        self.uint_value.unwrap_or(0)
+    }
 
-        pub fn double_value(&self) -> f64 {
-            self.double_value.unwrap_or(0.)
-        }
+    pub fn sint_value(&self) -> i64 {
+        self.sint_value.unwrap_or(0)
+    }
 
-        pub fn clear_double_value(&mut self) {
-            self.double_value = ::std::option::Option::None;
-        }
+    pub fn bool_value(&self) -> bool {
+        self.bool_value.unwrap_or(false)
+    }
+}
 
-        pub fn has_double_value(&self) -> bool {
-            self.double_value.is_some()
+impl Tile {
+    /// Unknown fields are skipped.
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        let mut tile = Self::default();
+        let mut r = Reader(data);
+        while let Some((field, wire)) = r.key()? {
+            match (field, wire) {
+                (3, LEN) => tile.layers.push(Layer::decode(r.bytes()?)?),
+                _ => r.skip(wire)?,
+            }
         }
+        Ok(tile)
+    }
 
-        // Param is passed by value, moved
-        pub fn set_double_value(&mut self, v: f64) {
-            self.double_value = ::std::option::Option::Some(v);
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        for layer in self.layers.iter() {
+            write_key(&mut buf, 3, LEN);
+            write_varint(&mut buf, layer.encoded_len() as u64);
+            layer.write(&mut buf);
         }
+        buf
+    }
 
-        // optional int64 int_value = 4;
-
-        pub fn int_value(&self) -> i64 {
-            self.int_value.unwrap_or(0)
-        }
+    fn encoded_len(&self) -> usize {
+        self.layers.iter().map(|l| len_field(l.encoded_len())).sum()
+    }
+}
 
-        pub fn clear_int_value(&mut self) {
-            self.int_value = ::std::option::Option::None;
-        }
+impl Layer {
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        let mut layer = Self::default();
+        let mut r = Reader(data);
+        while let Some((field, wire)) = r.key()? {
+            match (field, wire) {
+                (15, VARINT) => layer.version = Some(r.varint()? as u32),
+                (1, LEN) => layer.name = Some(r.string()?),
+                (2, LEN) => layer.features.push(Feature::decode(r.bytes()?)?),
+                (3, LEN) => layer.keys.push(r.string()?),
+                (4, LEN) => layer.values.push(Value::decode(r.bytes()?)?),
+                (5, VARINT) => layer.extent = Some(r.varint()? as u32),
+                _ => r.skip(wire)?,
+            }
+        }
+        Ok(layer)
+    }
 
-        pub fn has_int_value(&self) -> bool {
-            self.int_value.is_some()
+    fn encoded_len(&self) -> usize {
+        let mut len = 0;
+        if let Some(name) = &self.name {
+            len += len_field(name.len());
         }
-
-        // Param is passed by value, moved
-        pub fn set_int_value(&mut self, v: i64) {
-            self.int_value = ::std::option::Option::Some(v);
+        for f in self.features.iter() {
+            len += len_field(f.encoded_len());
         }
-
-        // optional uint64 uint_value = 5;
-
-        pub fn uint_value(&self) -> u64 {
-            self.uint_value.unwrap_or(0)
+        for k in self.keys.iter() {
+            len += len_field(k.len());
         }
-
-        pub fn clear_uint_value(&mut self) {
-            self.uint_value = ::std::option::Option::None;
+        for v in self.values.iter() {
+            len += len_field(v.encoded_len());
         }
-
-        pub fn has_uint_value(&self) -> bool {
-            self.uint_value.is_some()
+        if let Some(extent) = self.extent {
+            len += 1 + varint_len(extent as u64);
         }
-
-        // Param is passed by value, moved
-        pub fn set_uint_value(&mut self, v: u64) {
-            self.uint_value = ::std::option::Option::Some(v);
+        if let Some(version) = self.version {
+            len += 1 + varint_len(version as u64);
         }
+        len
+    }
 
-        // optional sint64 sint_value = 6;
-
-        pub fn sint_value(&self) -> i64 {
-            self.sint_value.unwrap_or(0)
+    fn write(&self, buf: &mut Vec<u8>) {
+        if let Some(name) = &self.name {
+            write_bytes(buf, 1, name.as_bytes());
         }
-
-        pub fn clear_sint_value(&mut self) {
-            self.sint_value = ::std::option::Option::None;
+        for f in self.features.iter() {
+            write_key(buf, 2, LEN);
+            write_varint(buf, f.encoded_len() as u64);
+            f.write(buf);
         }
-
-        pub fn has_sint_value(&self) -> bool {
-            self.sint_value.is_some()
+        for k in self.keys.iter() {
+            write_bytes(buf, 3, k.as_bytes());
         }
-
-        // Param is passed by value, moved
-        pub fn set_sint_value(&mut self, v: i64) {
-            self.sint_value = ::std::option::Option::Some(v);
+        for v in self.values.iter() {
+            write_key(buf, 4, LEN);
+            write_varint(buf, v.encoded_len() as u64);
+            v.write(buf);
         }
-
-        // optional bool bool_value = 7;
-
-        pub fn bool_value(&self) -> bool {
-            self.bool_value.unwrap_or(false)
+        if let Some(extent) = self.extent {
+            write_key(buf, 5, VARINT);
+            write_varint(buf, extent as u64);
         }
-
-        pub fn clear_bool_value(&mut self) {
-            self.bool_value = ::std::option::Option::None;
+        if let Some(version) = self.version {
+            write_key(buf, 15, VARINT);
+            write_varint(buf, version as u64);
         }
+    }
+}
 
-        pub fn has_bool_value(&self) -> bool {
-            self.bool_value.is_some()
+impl Feature {
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        let mut feature = Self::default();
+        let mut r = Reader(data);
+        while let Some((field, wire)) = r.key()? {
+            match (field, wire) {
+                (1, VARINT) => feature.id = Some(r.varint()?),
+                (2, LEN) => r.packed(&mut feature.tags)?,
+                (2, VARINT) => feature.tags.push(r.varint()? as u32),
+                (3, VARINT) => {
+                    feature.geom_type = Some(GeomType::from_u64(r.varint()?))
+                }
+                (4, LEN) => r.packed(&mut feature.geometry)?,
+                (4, VARINT) => feature.geometry.push(r.varint()? as u32),
+                _ => r.skip(wire)?,
+            }
         }
+        Ok(feature)
+    }
 
-        // Param is passed by value, moved
-        pub fn set_bool_value(&mut self, v: bool) {
-            self.bool_value = ::std::option::Option::Some(v);
-        }
+    fn encoded_len(&self) -> usize {
+        feature_len(self.id, self.geom_type, &self.tags, &self.geometry)
     }
 
-    impl ::protobuf::Message for Value {
-        const NAME: &'static str = "Value";
+    fn write(&self, buf: &mut Vec<u8>) {
+        write_feature(buf, self.id, self.geom_type, &self.tags, &self.geometry)
+    }
+}
 
-        fn is_initialized(&self) -> bool {
-            true
-        }
+fn feature_len(
+    id: Option<u64>, geom_type: Option<GeomType>, tags: &[u32],
+    geometry: &[u32],
+) -> usize {
+    let mut len = 0;
+    if let Some(id) = id {
+        len += 1 + varint_len(id);
+    }
+    if !tags.is_empty() {
+        len += len_field(packed_len(tags));
+    }
+    if let Some(t) = geom_type {
+        len += 1 + varint_len(t.to_u64());
+    }
+    if !geometry.is_empty() {
+        len += len_field(packed_len(geometry));
+    }
+    len
+}
 
-        fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
-            while let Some(tag) = is.read_raw_tag_or_eof()? {
-                match tag {
-                    10 => {
-                        self.string_value = ::std::option::Option::Some(is.read_string()?);
-                    },
-                    21 => {
-                        self.float_value = ::std::option::Option::Some(is.read_float()?);
-                    },
-                    25 => {
-                        self.double_value = ::std::option::Option::Some(is.read_double()?);
-                    },
-                    32 => {
-                        self.int_value = ::std::option::Option::Some(is.read_int64()?);
-                    },
-                    40 => {
-                        self.uint_value = ::std::option::Option::Some(is.read_uint64()?);
-                    },
-                    48 => {
-                        self.sint_value = ::std::option::Option::Some(is.read_sint64()?);
-                    },
-                    56 => {
-                        self.bool_value = ::std::option::Option::Some(is.read_bool()?);
-                    },
-                    tag => {
-                        ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
-                    },
-                };
-            }
-            ::std::result::Result::Ok(())
-        }
+fn write_feature(
+    buf: &mut Vec<u8>, id: Option<u64>, geom_type: Option<GeomType>,
+    tags: &[u32], geometry: &[u32],
+) {
+    if let Some(id) = id {
+        write_key(buf, 1, VARINT);
+        write_varint(buf, id);
+    }
+    write_packed(buf, 2, tags);
+    if let Some(t) = geom_type {
+        write_key(buf, 3, VARINT);
+        write_varint(buf, t.to_u64());
+    }
+    write_packed(buf, 4, geometry);
+}
 
-        // Compute sizes of nested messages
-        #[allow(unused_variables)]
-        fn compute_size(&self) -> u64 {
-            let mut my_size = 0;
-            if let Some(v) = self.string_value.as_ref() {
-                my_size += ::protobuf::rt::string_size(1, &v);
-            }
-            if let Some(v) = self.float_value {
-                my_size += 1 + 4;
-            }
-            if let Some(v) = self.double_value {
-                my_size += 1 + 8;
-            }
-            if let Some(v) = self.int_value {
-                my_size += ::protobuf::rt::int64_size(4, v);
-            }
-            if let Some(v) = self.uint_value {
-                my_size += ::protobuf::rt::uint64_size(5, v);
-            }
-            if let Some(v) = self.sint_value {
-                my_size += ::protobuf::rt::sint64_size(6, v);
-            }
-            if let Some(v) = self.bool_value {
-                my_size += 1 + 1;
+impl Value {
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        let mut value = Self::default();
+        let mut r = Reader(data);
+        while let Some((field, wire)) = r.key()? {
+            match (field, wire) {
+                (1, LEN) => value.string_value = Some(r.string()?),
+                (2, FIXED32) => {
+                    value.float_value = Some(f32::from_le_bytes(r.fixed()?))
+                }
+                (3, FIXED64) => {
+                    value.double_value = Some(f64::from_le_bytes(r.fixed()?))
+                }
+                (4, VARINT) => value.int_value = Some(r.varint()? as i64),
+                (5, VARINT) => value.uint_value = Some(r.varint()?),
+                (6, VARINT) => {
+                    let v = r.varint()?;
+                    value.sint_value = Some((v >> 1) as i64 ^ -((v & 1) as i64))
+                }
+                (7, VARINT) => value.bool_value = Some(r.varint()? != 0),
+                _ => r.skip(wire)?,
             }
-            my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
-            self.special_fields.cached_size().set(my_size as u32);
-            my_size
         }
+        Ok(value)
+    }
 
-        fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
-            if let Some(v) = self.string_value.as_ref() {
-                os.write_string(1, v)?;
-            }
-            if let Some(v) = self.float_value {
-                os.write_float(2, v)?;
-            }
-            if let Some(v) = self.double_value {
-                os.write_double(3, v)?;
-            }
-            if let Some(v) = self.int_value {
-                os.write_int64(4, v)?;
-            }
-            if let Some(v) = self.uint_value {
-                os.write_uint64(5, v)?;
-            }
-            if let Some(v) = self.sint_value {
-                os.write_sint64(6, v)?;
-            }
-            if let Some(v) = self.bool_value {
-                os.write_bool(7, v)?;
-            }
-            os.write_unknown_fields(self.special_fields.unknown_fields())?;
-            ::std::result::Result::Ok(())
+    fn encoded_len(&self) -> usize {
+        let mut len = 0;
+        if let Some(v) = &self.string_value {
+            len += len_field(v.len());
         }
-
-        fn special_fields(&self) -> &::protobuf::SpecialFields {
-            &self.special_fields
+        if self.float_value.is_some() {
+            len += 5;
         }
-
-        fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
-            &mut self.special_fields
+        if self.double_value.is_some() {
+            len += 9;
         }
-
-        fn new() -> Value {
-            Value::new()
+        if let Some(v) = self.int_value {
+            len += 1 + varint_len(v as u64);
         }
-
-        fn clear(&mut self) {
-            self.string_value = ::std::option::Option::None;
-            self.float_value = ::std::option::Option::None;
-            self.double_value = ::std::option::Option::None;
-            self.int_value = ::std::option::Option::None;
-            self.uint_value = ::std::option::Option::None;
-            self.sint_value = ::std::option::Option::None;
-            self.bool_value = ::std::option::Option::None;
-            self.special_fields.clear();
+        if let Some(v) = self.uint_value {
+            len += 1 + varint_len(v);
         }
-
-        fn default_instance() -> &'static Value {
-            static instance: Value = Value {
-                string_value: ::std::option::Option::None,
-                float_value: ::std::option::Option::None,
-                double_value: ::std::option::Option::None,
-                int_value: ::std::option::Option::None,
-                uint_value: ::std::option::Option::None,
-                sint_value: ::std::option::Option::None,
-                bool_value: ::std::option::Option::None,
-                special_fields: ::protobuf::SpecialFields::new(),
-            };
-            &instance
+        if let Some(v) = self.sint_value {
+            len += 1 + varint_len(zigzag(v));
         }
-    }
-
-    ///  Features are described in section 4.2 of the specification
-    // @@protoc_insertion_point(message:vector_tile.Tile.Feature)
-    #[derive(PartialEq,Clone,Default,Debug)]
-    pub struct Feature {
-        // message fields
-        // @@protoc_insertion_point(field:vector_tile.Tile.Feature.id)
-        pub id: ::std::option::Option<u64>,
-        ///  Tags of this feature are encoded as repeated pairs of
-        ///  integers.
-        ///  A detailed description of tags is located in sections
-        ///  4.2 and 4.4 of the specification
-        // @@protoc_insertion_point(field:vector_tile.Tile.Feature.tags)
-        pub tags: ::std::vec::Vec<u32>,
-        ///  The type of geometry stored in this feature.
-        // @@protoc_insertion_point(field:vector_tile.Tile.Feature.type)
-        pub type_: ::std::option::Option<::protobuf::EnumOrUnknown<GeomType>>,
-        ///  Contains a stream of commands and parameters (vertices).
-        ///  A detailed description on geometry encoding is located in 
-        ///  section 4.3 of the specification.
-        // @@protoc_insertion_point(field:vector_tile.Tile.Feature.geometry)
-        pub geometry: ::std::vec::Vec<u32>,
-        // special fields
-        // @@protoc_insertion_point(special_field:vector_tile.Tile.Feature.special_fields)
-        pub special_fields: ::protobuf::SpecialFields,
-    }
-
-    impl<'a> ::std::default::Default for &'a Feature {
-        fn default() -> &'a Feature {
-            <Feature as ::protobuf::Message>::default_instance()
+        if self.bool_value.is_some() {
+            len += 2;
         }
+        len
     }
 
-    impl Feature {
-        pub fn new() -> Feature {
-            ::std::default::Default::default()
+    fn write(&self, buf: &mut Vec<u8>) {
+        if let Some(v) = &self.string_value {
+            write_bytes(buf, 1, v.as_bytes());
         }
-
-        // optional uint64 id = 1;
-
-        pub fn id(&self) -> u64 {
-            self.id.unwrap_or(0u64)
+        if let Some(v) = self.float_value {
+            write_key(buf, 2, FIXED32);
+            buf.extend_from_slice(&v.to_le_bytes());
         }
-
-        pub fn clear_id(&mut self) {
-            self.id = ::std::option::Option::None;
+        if let Some(v) = self.double_value {
+            write_key(buf, 3, FIXED64);
+            buf.extend_from_slice(&v.to_le_bytes());
         }
-
-        pub fn has_id(&self) -> bool {
-            self.id.is_some()
+        if let Some(v) = self.int_value {
+            write_key(buf, 4, VARINT);
+            write_varint(buf, v as u64);
         }
-
-        // Param is passed by value, moved
-        pub fn set_id(&mut self, v: u64) {
-            self.id = ::std::option::Option::Some(v);
+        if let Some(v) = self.uint_value {
+            write_key(buf, 5, VARINT);
+            write_varint(buf, v);
         }
-
-        // optional .vector_tile.Tile.GeomType type = 3;
-
-        pub fn type_(&self) -> GeomType {
-            match self.type_ {
-                Some(e) => e.enum_value_or(GeomType::UNKNOWN),
-                None => GeomType::UNKNOWN,
-            }
+        if let Some(v) = self.sint_value {
+            write_key(buf, 6, VARINT);
+            write_varint(buf, zigzag(v));
         }
-
-        pub fn clear_type_(&mut self) {
-            self.type_ = ::std::option::Option::None;
+        if let Some(v) = self.bool_value {
+            write_key(buf, 7, VARINT);
+            write_varint(buf, v as u64);
         }
+    }
+}
 
-        pub fn has_type(&self) -> bool {
-            self.type_.is_some()
-        }
+/// A borrowed [`Value`], written without cloning its string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueRef<'a> {
+    String(&'a str),
+    Float(f32),
+    Double(f64),
+    Int(i64),
+    Uint(u64),
+    Sint(i64),
+    Bool(bool),
+}
 
-        // Param is passed by value, moved
-        pub fn set_type(&mut self, v: GeomType) {
-            self.type_ = ::std::option::Option::Some(::protobuf::EnumOrUnknown::new(v));
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    /// the first field set, a value without any becomes an empty string
+    fn from(v: &'a Value) -> Self {
+        if let Some(v) = &v.string_value {
+            Self::String(v)
+        } else if let Some(v) = v.float_value {
+            Self::Float(v)
+        } else if let Some(v) = v.double_value {
+            Self::Double(v)
+        } else if let Some(v) = v.int_value {
+            Self::Int(v)
+        } else if let Some(v) = v.uint_value {
+            Self::Uint(v)
+        } else if let Some(v) = v.sint_value {
+            Self::Sint(v)
+        } else if let Some(v) = v.bool_value {
+            Self::Bool(v)
+        } else {
+            Self::String("")
         }
     }
+}
 
-    impl ::protobuf::Message for Feature {
-        const NAME: &'static str = "Feature";
-
-        fn is_initialized(&self) -> bool {
-            true
+impl ValueRef<'_> {
+    fn encoded_len(&self) -> usize {
+        match self {
+            Self::String(v) => len_field(v.len()),
+            Self::Float(_) => 5,
+            Self::Double(_) => 9,
+            Self::Int(v) => 1 + varint_len(*v as u64),
+            Self::Uint(v) => 1 + varint_len(*v),
+            Self::Sint(v) => 1 + varint_len(zigzag(*v)),
+            Self::Bool(_) => 2,
         }
+    }
 
-        fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
-            while let Some(tag) = is.read_raw_tag_or_eof()? {
-                match tag {
-                    8 => {
-                        self.id = ::std::option::Option::Some(is.read_uint64()?);
-                    },
-                    18 => {
-                        is.read_repeated_packed_uint32_into(&mut self.tags)?;
-                    },
-                    16 => {
-                        self.tags.push(is.read_uint32()?);
-                    },
-                    24 => {
-                        self.type_ = ::std::option::Option::Some(is.read_enum_or_unknown()?);
-                    },
-                    34 => {
-                        is.read_repeated_packed_uint32_into(&mut self.geometry)?;
-                    },
-                    32 => {
-                        self.geometry.push(is.read_uint32()?);
-                    },
-                    tag => {
-                        ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
-                    },
-                };
+    fn write(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::String(v) => write_bytes(buf, 1, v.as_bytes()),
+            Self::Float(v) => {
+                write_key(buf, 2, FIXED32);
+                buf.extend_from_slice(&v.to_le_bytes());
             }
-            ::std::result::Result::Ok(())
-        }
-
-        // Compute sizes of nested messages
-        #[allow(unused_variables)]
-        fn compute_size(&self) -> u64 {
-            let mut my_size = 0;
-            if let Some(v) = self.id {
-                my_size += ::protobuf::rt::uint64_size(1, v);
+            Self::Double(v) => {
+                write_key(buf, 3, FIXED64);
+                buf.extend_from_slice(&v.to_le_bytes());
             }
-            my_size += ::protobuf::rt::vec_packed_uint32_size(2, &self.tags);
-            if let Some(v) = self.type_ {
-                my_size += ::protobuf::rt::int32_size(3, v.value());
+            Self::Int(v) => {
+                write_key(buf, 4, VARINT);
+                write_varint(buf, *v as u64);
             }
-            my_size += ::protobuf::rt::vec_packed_uint32_size(4, &self.geometry);
-            my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
-            self.special_fields.cached_size().set(my_size as u32);
-            my_size
-        }
-
-        fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
-            if let Some(v) = self.id {
-                os.write_uint64(1, v)?;
+            Self::Uint(v) => {
+                write_key(buf, 5, VARINT);
+                write_varint(buf, *v);
             }
-            os.write_repeated_packed_uint32(2, &self.tags)?;
-            if let Some(v) = self.type_ {
-                os.write_enum(3, ::protobuf::EnumOrUnknown::value(&v))?;
+            Self::Sint(v) => {
+                write_key(buf, 6, VARINT);
+                write_varint(buf, zigzag(*v));
+            }
+            Self::Bool(v) => {
+                write_key(buf, 7, VARINT);
+                write_varint(buf, *v as u64);
             }
-            os.write_repeated_packed_uint32(4, &self.geometry)?;
-            os.write_unknown_fields(self.special_fields.unknown_fields())?;
-            ::std::result::Result::Ok(())
-        }
-
-        fn special_fields(&self) -> &::protobuf::SpecialFields {
-            &self.special_fields
-        }
-
-        fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
-            &mut self.special_fields
-        }
-
-        fn new() -> Feature {
-            Feature::new()
-        }
-
-        fn clear(&mut self) {
-            self.id = ::std::option::Option::None;
-            self.tags.clear();
-            self.type_ = ::std::option::Option::None;
-            self.geometry.clear();
-            self.special_fields.clear();
-        }
-
-        fn default_instance() -> &'static Feature {
-            static instance: Feature = Feature {
-                id: ::std::option::Option::None,
-                tags: ::std::vec::Vec::new(),
-                type_: ::std::option::Option::None,
-                geometry: ::std::vec::Vec::new(),
-                special_fields: ::protobuf::SpecialFields::new(),
-            };
-            &instance
         }
     }
+}
 
-    ///  Layers are described in section 4.1 of the specification
-    // @@protoc_insertion_point(message:vector_tile.Tile.Layer)
-    #[derive(PartialEq,Clone,Default,Debug)]
-    pub struct Layer {
-        // message fields
-        ///  Any compliant implementation must first read the version
-        ///  number encoded in this message and choose the correct
-        ///  implementation for this version number before proceeding to
-        ///  decode other parts of this message.
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.version)
-        pub version: ::std::option::Option<u32>,
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.name)
-        pub name: ::std::option::Option<::std::string::String>,
-        ///  The actual features in this tile.
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.features)
-        pub features: ::std::vec::Vec<Feature>,
-        ///  Dictionary encoding for keys
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.keys)
-        pub keys: ::std::vec::Vec<::std::string::String>,
-        ///  Dictionary encoding for values
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.values)
-        pub values: ::std::vec::Vec<Value>,
-        ///  Although this is an "optional" field it is required by the specification.
-        ///  See https://github.com/mapbox/vector-tile-spec/issues/47
-        // @@protoc_insertion_point(field:vector_tile.Tile.Layer.extent)
-        pub extent: ::std::option::Option<u32>,
-        // special fields
-        // @@protoc_insertion_point(special_field:vector_tile.Tile.Layer.special_fields)
-        pub special_fields: ::protobuf::SpecialFields,
+/// Streams a version 2 `P::NAME` layer with an extent of 4096 into `w`.
+///
+/// Lengths are computed in a first pass over `points`, which also keeps the
+/// geometry of every feature and the encoded values table for the second
+/// one. features are never held in memory as a whole. every value gets its
+/// own entry in the values table. `w` is written once per feature, wrap
+/// files in a [`BufWriter`](std::io::BufWriter).
+pub fn write_layer<'a, P, W, I>(w: &mut W, points: I) -> io::Result<()>
+where
+    P: Point + 'a,
+    W: Write + ?Sized,
+    I: Iterator<Item = (&'a P, &'a Coordinate)> + Clone,
+{
+    let keys = P::KEYS.len() as u32;
+    let mut tags = Vec::<u32>::with_capacity(P::KEYS.len() * 2);
+    let fill_tags = |tags: &mut Vec<u32>, idx: usize| {
+        tags.clear();
+        for k in 0..keys {
+            tags.push(k);
+            tags.push(idx as u32 * keys + k);
+        }
+    };
+    let point = Some(GeomType::Point);
+
+    // length and geometry of every feature
+    let mut features = Vec::<(usize, [u32; 3])>::new();
+    let mut values = Vec::<u8>::new();
+    let mut len = len_field(P::NAME.len());
+    for (idx, (p, c)) in points.clone().enumerate() {
+        fill_tags(&mut tags, idx);
+        let geometry = c.to_geometry();
+        let feature = feature_len(p.id(), point, &tags, &geometry);
+        len += len_field(feature);
+        features.push((feature, geometry));
+        p.for_each_value(|v| {
+            write_key(&mut values, 4, LEN);
+            write_varint(&mut values, v.encoded_len() as u64);
+            v.write(&mut values);
+        });
     }
-
-    impl<'a> ::std::default::Default for &'a Layer {
-        fn default() -> &'a Layer {
-            <Layer as ::protobuf::Message>::default_instance()
-        }
+    len += values.len();
+    len += P::KEYS.iter().map(|k| len_field(k.len())).sum::<usize>();
+    len += 1 + varint_len(4096) + 1 + varint_len(2);
+
+    let mut buf = Vec::with_capacity(64);
+    write_key(&mut buf, 3, LEN);
+    write_varint(&mut buf, len as u64);
+    write_bytes(&mut buf, 1, P::NAME.as_bytes());
+    w.write_all(&buf)?;
+
+    for (idx, ((p, _), (feature, geometry))) in points.zip(features).enumerate()
+    {
+        fill_tags(&mut tags, idx);
+        buf.clear();
+        write_key(&mut buf, 2, LEN);
+        write_varint(&mut buf, feature as u64);
+        write_feature(&mut buf, p.id(), point, &tags, &geometry);
+        w.write_all(&buf)?;
     }
 
-    impl Layer {
-        pub fn new() -> Layer {
-            ::std::default::Default::default()
-        }
-
-        // required uint32 version = 15;
-
-        pub fn version(&self) -> u32 {
-            self.version.unwrap_or(1u32)
-        }
-
-        pub fn clear_version(&mut self) {
-            self.version = ::std::option::Option::None;
-        }
-
-        pub fn has_version(&self) -> bool {
-            self.version.is_some()
-        }
-
-        // Param is passed by value, moved
-        pub fn set_version(&mut self, v: u32) {
-            self.version = ::std::option::Option::Some(v);
-        }
+    buf.clear();
+    for key in P::KEYS {
+        write_bytes(&mut buf, 3, key.as_bytes());
+    }
+    w.write_all(&buf)?;
+    w.write_all(&values)?;
+
+    buf.clear();
+    write_key(&mut buf, 5, VARINT);
+    write_varint(&mut buf, 4096);
+    write_key(&mut buf, 15, VARINT);
+    write_varint(&mut buf, 2);
+    w.write_all(&buf)
+}
 
-        // required string name = 1;
+struct Reader<'a>(&'a [u8]);
 
-        pub fn name(&self) -> &str {
-            match self.name.as_ref() {
-                Some(v) => v,
-                None => "",
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut v = 0u64;
+        for (idx, b) in self.0.iter().enumerate().take(10) {
+            v |= ((b & 0x7f) as u64) << (idx * 7);
+            if b & 0x80 == 0 {
+                self.0 = &self.0[idx + 1..];
+                return Ok(v);
             }
         }
+        Err(if self.0.len() < 10 {
+            Error::UnexpectedEof
+        } else {
+            Error::Varint
+        })
+    }
 
-        pub fn clear_name(&mut self) {
-            self.name = ::std::option::Option::None;
-        }
-
-        pub fn has_name(&self) -> bool {
-            self.name.is_some()
-        }
-
-        // Param is passed by value, moved
-        pub fn set_name(&mut self, v: ::std::string::String) {
-            self.name = ::std::option::Option::Some(v);
-        }
-
-        // Mutable pointer to the field.
-        // If field is not initialized, it is initialized with default value first.
-        pub fn mut_name(&mut self) -> &mut ::std::string::String {
-            if self.name.is_none() {
-                self.name = ::std::option::Option::Some(::std::string::String::new());
-            }
-            self.name.as_mut().unwrap()
+    /// field number and wire type of the next field
+    fn key(&mut self) -> Result<Option<(u64, u8)>, Error> {
+        if self.0.is_empty() {
+            return Ok(None);
         }
+        let key = self.varint()?;
+        Ok(Some((key >> 3, (key & 0x7) as u8)))
+    }
 
-        // Take field
-        pub fn take_name(&mut self) -> ::std::string::String {
-            self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(Error::UnexpectedEof);
         }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
 
-        // optional uint32 extent = 5;
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.varint()?;
+        self.take(usize::try_from(len).map_err(|_| Error::UnexpectedEof)?)
+    }
 
-        pub fn extent(&self) -> u32 {
-            self.extent.unwrap_or(4096u32)
-        }
+    fn string(&mut self) -> Result<String, Error> {
+        let bytes = self.bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::Utf8)
+    }
 
-        pub fn clear_extent(&mut self) {
-            self.extent = ::std::option::Option::None;
-        }
+    fn fixed<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.take(N)?.try_into().unwrap_or([0; N]))
+    }
 
-        pub fn has_extent(&self) -> bool {
-            self.extent.is_some()
+    fn packed(&mut self, out: &mut Vec<u32>) -> Result<(), Error> {
+        let mut r = Reader(self.bytes()?);
+        out.reserve(r.0.len());
+        while !r.0.is_empty() {
+            out.push(r.varint()? as u32);
         }
+        Ok(())
+    }
 
-        // Param is passed by value, moved
-        pub fn set_extent(&mut self, v: u32) {
-            self.extent = ::std::option::Option::Some(v);
+    fn skip(&mut self, wire: u8) -> Result<(), Error> {
+        match wire {
+            VARINT => self.varint().map(drop),
+            FIXED64 => self.take(8).map(drop),
+            LEN => self.bytes().map(drop),
+            FIXED32 => self.take(4).map(drop),
+            _ => Err(Error::WireType(wire)),
         }
     }
+}
 
-    impl ::protobuf::Message for Layer {
-        const NAME: &'static str = "Layer";
-
-        fn is_initialized(&self) -> bool {
-            if self.version.is_none() {
-                return false;
-            }
-            if self.name.is_none() {
-                return false;
-            }
-            for v in &self.features {
-                if !v.is_initialized() {
-                    return false;
-                }
-            };
-            for v in &self.values {
-                if !v.is_initialized() {
-                    return false;
-                }
-            };
-            true
-        }
-
-        fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
-            while let Some(tag) = is.read_raw_tag_or_eof()? {
-                match tag {
-                    120 => {
-                        self.version = ::std::option::Option::Some(is.read_uint32()?);
-                    },
-                    10 => {
-                        self.name = ::std::option::Option::Some(is.read_string()?);
-                    },
-                    18 => {
-                        self.features.push(is.read_message()?);
-                    },
-                    26 => {
-                        self.keys.push(is.read_string()?);
-                    },
-                    34 => {
-                        self.values.push(is.read_message()?);
-                    },
-                    40 => {
-                        self.extent = ::std::option::Option::Some(is.read_uint32()?);
-                    },
-                    tag => {
-                        ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
-                    },
-                };
-            }
-            ::std::result::Result::Ok(())
-        }
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
 
-        // Compute sizes of nested messages
-        #[allow(unused_variables)]
-        fn compute_size(&self) -> u64 {
-            let mut my_size = 0;
-            if let Some(v) = self.version {
-                my_size += ::protobuf::rt::uint32_size(15, v);
-            }
-            if let Some(v) = self.name.as_ref() {
-                my_size += ::protobuf::rt::string_size(1, &v);
-            }
-            for value in &self.features {
-                let len = value.compute_size();
-                my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
-            };
-            for value in &self.keys {
-                my_size += ::protobuf::rt::string_size(3, &value);
-            };
-            for value in &self.values {
-                let len = value.compute_size();
-                my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
-            };
-            if let Some(v) = self.extent {
-                my_size += ::protobuf::rt::uint32_size(5, v);
-            }
-            my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
-            self.special_fields.cached_size().set(my_size as u32);
-            my_size
-        }
+fn varint_len(v: u64) -> usize {
+    (64 - (v | 1).leading_zeros() as usize).div_ceil(7)
+}
 
-        fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
-            if let Some(v) = self.version {
-                os.write_uint32(15, v)?;
-            }
-            if let Some(v) = self.name.as_ref() {
-                os.write_string(1, v)?;
-            }
-            for v in &self.features {
-                ::protobuf::rt::write_message_field_with_cached_size(2, v, os)?;
-            };
-            for v in &self.keys {
-                os.write_string(3, &v)?;
-            };
-            for v in &self.values {
-                ::protobuf::rt::write_message_field_with_cached_size(4, v, os)?;
-            };
-            if let Some(v) = self.extent {
-                os.write_uint32(5, v)?;
-            }
-            os.write_unknown_fields(self.special_fields.unknown_fields())?;
-            ::std::result::Result::Ok(())
-        }
+/// length of a length delimited field with a single byte key
+fn len_field(len: usize) -> usize {
+    1 + varint_len(len as u64) + len
+}
 
-        fn special_fields(&self) -> &::protobuf::SpecialFields {
-            &self.special_fields
-        }
+fn packed_len(values: &[u32]) -> usize {
+    values.iter().map(|v| varint_len(*v as u64)).sum()
+}
 
-        fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
-            &mut self.special_fields
-        }
+fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
 
-        fn new() -> Layer {
-            Layer::new()
-        }
+fn write_key(buf: &mut Vec<u8>, field: u64, wire: u8) {
+    write_varint(buf, field << 3 | wire as u64);
+}
 
-        fn clear(&mut self) {
-            self.version = ::std::option::Option::None;
-            self.name = ::std::option::Option::None;
-            self.features.clear();
-            self.keys.clear();
-            self.values.clear();
-            self.extent = ::std::option::Option::None;
-            self.special_fields.clear();
-        }
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_key(buf, field, LEN);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
 
-        fn default_instance() -> &'static Layer {
-            static instance: Layer = Layer {
-                version: ::std::option::Option::None,
-                name: ::std::option::Option::None,
-                features: ::std::vec::Vec::new(),
-                keys: ::std::vec::Vec::new(),
-                values: ::std::vec::Vec::new(),
-                extent: ::std::option::Option::None,
-                special_fields: ::protobuf::SpecialFields::new(),
-            };
-            &instance
-        }
+fn write_packed(buf: &mut Vec<u8>, field: u64, values: &[u32]) {
+    if values.is_empty() {
+        return;
     }
-
-    ///  GeomType is described in section 4.3.4 of the specification
-    #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
-    // @@protoc_insertion_point(enum:vector_tile.Tile.GeomType)
-    pub enum GeomType {
-        // @@protoc_insertion_point(enum_value:vector_tile.Tile.GeomType.UNKNOWN)
-        UNKNOWN = 0,
-        // @@protoc_insertion_point(enum_value:vector_tile.Tile.GeomType.POINT)
-        POINT = 1,
-        // @@protoc_insertion_point(enum_value:vector_tile.Tile.GeomType.LINESTRING)
-        LINESTRING = 2,
-        // @@protoc_insertion_point(enum_value:vector_tile.Tile.GeomType.POLYGON)
-        POLYGON = 3,
+    write_key(buf, field, LEN);
+    write_varint(buf, packed_len(values) as u64);
+    for v in values {
+        write_varint(buf, *v as u64);
     }
+}
 
-    impl ::protobuf::Enum for GeomType {
-        const NAME: &'static str = "GeomType";
-
-        fn value(&self) -> i32 {
-            *self as i32
-        }
-
-        fn from_i32(value: i32) -> ::std::option::Option<GeomType> {
-            match value {
-                0 => ::std::option::Option::Some(GeomType::UNKNOWN),
-                1 => ::std::option::Option::Some(GeomType::POINT),
-                2 => ::std::option::Option::Some(GeomType::LINESTRING),
-                3 => ::std::option::Option::Some(GeomType::POLYGON),
-                _ => ::std::option::Option::None
-            }
-        }
-
-        fn from_str(str: &str) -> ::std::option::Option<GeomType> {
-            match str {
-                "UNKNOWN" => ::std::option::Option::Some(GeomType::UNKNOWN),
-                "POINT" => ::std::option::Option::Some(GeomType::POINT),
-                "LINESTRING" => ::std::option::Option::Some(GeomType::LINESTRING),
-                "POLYGON" => ::std::option::Option::Some(GeomType::POLYGON),
-                _ => ::std::option::Option::None
-            }
-        }
-
-        const VALUES: &'static [GeomType] = &[
-            GeomType::UNKNOWN,
-            GeomType::POINT,
-            GeomType::LINESTRING,
-            GeomType::POLYGON,
+#[cfg(test)]
+mod tests {
+    use super::{Feature, GeomType, Layer, Tile, Value};
+    use crate::Error;
+
+    #[test]
+    fn roundtrip() {
+        let layer = Layer {
+            version: Some(2),
+            name: Some(String::from("poi")),
+            features: vec![
+                Feature {
+                    id: Some(u64::MAX),
+                    tags: vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6],
+                    geom_type: Some(GeomType::Point),
+                    geometry: vec![9, 50, 34],
+                },
+                Feature {
+                    geom_type: Some(GeomType::Polygon),
+                    ..Default::default()
+                },
+            ],
+            keys: ["s", "f", "d", "i", "u", "z", "b"]
+                .map(String::from)
+                .to_vec(),
+            values: vec![
+                Value::from_string(String::from("caf\u{e9}")),
+                Value { float_value: Some(1.5), ..Default::default() },
+                Value { double_value: Some(-2.25), ..Default::default() },
+                Value::from_int(-300),
+                Value::from_uint(1 << 40),
+                Value { sint_value: Some(-7), ..Default::default() },
+                Value::from_bool(true),
+            ],
+            extent: Some(4096),
+        };
+        let tile = Tile { layers: vec![layer.clone(), Layer::default()] };
+
+        let pbf = tile.encode();
+        assert_eq!(Tile::decode(&pbf).unwrap(), tile);
+
+        // proto2 parsers must accept unpacked repeated fields and skip
+        // unknown ones
+        let raw = [
+            0x1a, 0x0d, // layer
+            0x12, 0x08, // feature
+            0x10, 0x01, 0x10, 0x02, // unpacked tags
+            0x18, 0x07, // unknown geometry type
+            0x50, 0x01, // unknown field 10
+            0x28, 0x80, 0x20, // extent 4096
         ];
+        let decoded = Tile::decode(&raw).unwrap();
+        assert_eq!(decoded.layers[0].features[0].tags, [1, 2]);
+        assert_eq!(
+            decoded.layers[0].features[0].geom_type(),
+            GeomType::Unknown
+        );
+        assert_eq!(decoded.layers[0].extent, Some(4096));
+
+        assert!(matches!(
+            Tile::decode(&pbf[..pbf.len() - 1]),
+            Err(Error::UnexpectedEof)
+        ));
+        assert!(matches!(Tile::decode(&[0x1b]), Err(Error::WireType(3))));
+        assert!(matches!(Tile::decode(&[0xff; 11]), Err(Error::Varint)));
     }
-
-    impl ::std::default::Default for GeomType {
-        fn default() -> Self {
-            GeomType::UNKNOWN
-        }
-    }
-
 }
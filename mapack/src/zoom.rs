@@ -0,0 +1,81 @@
+use crate::{Error, Limit, PointTile, TileId};
+
+/// Derives the tile `czom/ctx/cty` from its ancestor `zom/tx/ty`.
+///
+/// The ancestor is decoded, every point is re-projected to `czom` and only
+/// the ones inside the child are kept. precision is that of the ancestor.
+/// a tile that is not a child of the ancestor, or a `czom` past the
+/// maximum zoom of [`Coordinate`](crate::Coordinate), comes out empty.
+pub fn overzoom<T: PointTile>(
+    zom: u8, tx: u32, ty: u32, pbf: Vec<u8>, czom: u8, ctx: u32, cty: u32,
+) -> Result<T, Error> {
+    let mut tile = T::decode(zom, tx, ty, pbf)?;
+    tile.retain_mut(|coordinate| {
+        coordinate.set_zoom(czom);
+        coordinate.txy() == (ctx, cty) && coordinate.zoom() == czom
+    });
+
+    Ok(tile)
+}
+
+/// Builds the tile `zom/tx/ty` from its four children.
+///
+/// `children` are the encoded tiles of `(2x, 2y)`, `(2x + 1, 2y)`,
+/// `(2x, 2y + 1)` and `(2x + 1, 2y + 1)` on `zom + 1`, missing ones are
+/// skipped. every point is re-projected to `zom` and the merged tile is
+/// thinned with `limit`, if any, down to its `max_bytes` as well. a tile
+/// without children, see [`TileId::children`], comes out empty.
+///
+/// The children are interleaved in the order `(2x, 2y)`, `(2x + 1, 2y + 1)`,
+/// `(2x + 1, 2y)` and `(2x, 2y + 1)`, so thinning takes points from every
+/// child in turn instead of dropping the last children whole.
+pub fn merge_children<T: PointTile>(
+    zom: u8, tx: u32, ty: u32, mut children: [Option<Vec<u8>>; 4],
+    limit: Option<&Limit>,
+) -> Result<Vec<u8>, Error> {
+    let mut tile = T::new();
+
+    let Some(ids) = TileId::new(zom, tx, ty).children() else {
+        return Ok(tile.encode());
+    };
+    // diagonal first, so the first two points span both rows and columns
+    let mut decoded = Vec::with_capacity(4);
+    for i in [0, 3, 1, 2] {
+        let Some(pbf) = children[i].take() else { continue };
+
+        let id = ids[i];
+        let mut child = T::decode(id.z, id.x, id.y, pbf)?;
+        child.retain_mut(|coordinate| {
+            coordinate.set_zoom(zom);
+            true
+        });
+        decoded.push(child);
+    }
+    tile.interleave(&mut decoded);
+
+    let Some(limit) = limit else { return Ok(tile.encode()) };
+    tile.apply_limit(limit);
+
+    // same as the pyramid, drop points and encode again until the tile
+    // fits. `n` caps every layer, so it shrinks on every round
+    let mut data = tile.encode();
+    if let Some(max) = limit.max_bytes() {
+        let mut n = usize::MAX;
+        while data.len() > max {
+            let mut len = 0;
+            tile.retain_mut(|_| {
+                len += 1;
+                true
+            });
+            if len == 0 {
+                break;
+            }
+
+            n = (len * max / data.len()).min(len - 1).min(n.saturating_sub(1));
+            tile.apply_limit(&limit.clone().with_max_features(n));
+            data = tile.encode();
+        }
+    }
+
+    Ok(data)
+}